@@ -0,0 +1,1410 @@
+//! A month calendar, which can be embedded directly into a [`Ui`] or opened in the popup of a
+//! [`DatePicker`](crate::datepicker::DatePicker).
+//!
+//! ```no_run
+//! use chrono::prelude::*;
+//! use eframe::egui::Ui;
+//! use grus_gui_lib::calendar::Calendar;
+//! use core::ops::RangeInclusive;
+//!
+//! fn draw_sidebar(ui: &mut Ui, date: &mut NaiveDateTime) {
+//!     ui.add(Calendar::<RangeInclusive<NaiveDateTime>>::new("sidebar_calendar", date).months(2));
+//! }
+//! ```
+
+use core::ops::{Bound, RangeBounds, RangeInclusive};
+use std::collections::BTreeSet;
+use std::hash::Hash;
+
+use chrono::naive::NaiveDateTime;
+use chrono::{prelude::*, Duration, Months};
+use eframe::{
+	egui,
+	egui::{pos2, Align2, Color32, DragValue, FontId, Id, Key, Modifiers, Response, RichText, Ui, Widget},
+};
+
+/// Names and conventions used to present dates in a particular language and region.
+///
+/// A few common locales are provided as constants, others can be built by filling in the fields.
+/// Note that egui's default fonts do not contain CJK glyphs, so [`Locale::JA`] needs a font
+/// providing them to be installed with [`egui::Context::set_fonts`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Locale {
+	/// Short weekday names, starting with Monday.
+	pub weekday_names: [&'static str; 7],
+	/// Full month names, starting with January.
+	pub month_names: [&'static str; 12],
+	/// Abbreviated month names, starting with January.
+	pub short_month_names: [&'static str; 12],
+	/// The day weeks start with in the calendar.
+	pub first_weekday: Weekday,
+	/// Default format of the date shown on the date picker's button.
+	pub date_format: &'static str,
+	/// Header of the week number column.
+	pub week_label: &'static str,
+	/// Shown on the date picker's button while no date is selected.
+	pub placeholder: &'static str,
+	/// Labels of the date picker's footer buttons.
+	pub today: &'static str,
+	pub clear: &'static str,
+	pub cancel: &'static str,
+	pub ok: &'static str,
+	/// Label of the recurrence editor's interval.
+	pub repeat_every: &'static str,
+	/// Units of the recurrence interval: days, weeks, months and years.
+	pub frequency_units: [&'static str; 4],
+	/// Labels of the recurrence editor's end choices.
+	pub ends: &'static str,
+	pub ends_never: &'static str,
+	pub ends_after: &'static str,
+	pub ends_on: &'static str,
+	/// Suffix of the number of occurrences, including any space before it.
+	pub times_suffix: &'static str,
+}
+
+impl Locale {
+	pub const EN: Locale = Locale {
+		weekday_names: ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+		month_names: [
+			"January", "February", "March", "April", "May", "June",
+			"July", "August", "September", "October", "November", "December",
+		],
+		short_month_names: [
+			"Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+		],
+		first_weekday: Weekday::Mon,
+		date_format: "%Y-%m-%d",
+		week_label: "Wk",
+		placeholder: "Select date",
+		today: "Today",
+		clear: "Clear",
+		cancel: "Cancel",
+		ok: "OK",
+		repeat_every: "Repeat every",
+		frequency_units: ["days", "weeks", "months", "years"],
+		ends: "Ends",
+		ends_never: "never",
+		ends_after: "after",
+		ends_on: "on",
+		times_suffix: " times",
+	};
+
+	pub const DE: Locale = Locale {
+		weekday_names: ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"],
+		month_names: [
+			"Januar", "Februar", "März", "April", "Mai", "Juni",
+			"Juli", "August", "September", "Oktober", "November", "Dezember",
+		],
+		short_month_names: [
+			"Jan", "Feb", "Mär", "Apr", "Mai", "Jun", "Jul", "Aug", "Sep", "Okt", "Nov", "Dez",
+		],
+		first_weekday: Weekday::Mon,
+		date_format: "%d.%m.%Y",
+		week_label: "KW",
+		placeholder: "Datum wählen",
+		today: "Heute",
+		clear: "Leeren",
+		cancel: "Abbrechen",
+		ok: "OK",
+		repeat_every: "Wiederholen alle",
+		frequency_units: ["Tage", "Wochen", "Monate", "Jahre"],
+		ends: "Endet",
+		ends_never: "nie",
+		ends_after: "nach",
+		ends_on: "am",
+		times_suffix: " Mal",
+	};
+
+	pub const JA: Locale = Locale {
+		weekday_names: ["月", "火", "水", "木", "金", "土", "日"],
+		month_names: [
+			"1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月",
+		],
+		short_month_names: [
+			"1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月",
+		],
+		first_weekday: Weekday::Sun,
+		date_format: "%Y/%m/%d",
+		week_label: "週",
+		placeholder: "日付を選択",
+		today: "今日",
+		clear: "クリア",
+		cancel: "キャンセル",
+		ok: "OK",
+		repeat_every: "繰り返す間隔",
+		frequency_units: ["日", "週", "か月", "年"],
+		ends: "終了",
+		ends_never: "なし",
+		ends_after: "回数",
+		ends_on: "日付",
+		times_suffix: "回",
+	};
+
+	pub const PT_BR: Locale = Locale {
+		weekday_names: ["seg", "ter", "qua", "qui", "sex", "sáb", "dom"],
+		month_names: [
+			"janeiro", "fevereiro", "março", "abril", "maio", "junho",
+			"julho", "agosto", "setembro", "outubro", "novembro", "dezembro",
+		],
+		short_month_names: [
+			"jan", "fev", "mar", "abr", "mai", "jun", "jul", "ago", "set", "out", "nov", "dez",
+		],
+		first_weekday: Weekday::Sun,
+		date_format: "%d/%m/%Y",
+		week_label: "Sem",
+		placeholder: "Selecionar data",
+		today: "Hoje",
+		clear: "Limpar",
+		cancel: "Cancelar",
+		ok: "OK",
+		repeat_every: "Repetir a cada",
+		frequency_units: ["dias", "semanas", "meses", "anos"],
+		ends: "Termina",
+		ends_never: "nunca",
+		ends_after: "após",
+		ends_on: "em",
+		times_suffix: " vezes",
+	};
+
+	pub fn weekday_name(&self, weekday: Weekday) -> &'static str {
+		self.weekday_names[weekday.num_days_from_monday() as usize]
+	}
+
+	/// `month` is in the range `1..=12`, like [`Datelike::month`].
+	pub fn month_name(&self, month: u32) -> &'static str {
+		self.month_names[month as usize - 1]
+	}
+
+	/// `month` is in the range `1..=12`, like [`Datelike::month`].
+	pub fn short_month_name(&self, month: u32) -> &'static str {
+		self.short_month_names[month as usize - 1]
+	}
+
+	/// Parse a month number, or a full or abbreviated month name, ignoring case.
+	pub fn parse_month(&self, text: &str) -> Option<u32> {
+		let text = text.trim();
+		if let Ok(month) = text.parse::<u32>() {
+			return (1..=12).contains(&month).then_some(month);
+		}
+		let text = text.to_lowercase();
+		(1..=12).find(|&month| {
+			self.month_name(month).to_lowercase() == text
+				|| self.short_month_name(month).to_lowercase() == text
+		})
+	}
+}
+
+impl Default for Locale {
+	fn default() -> Self {
+		Self::EN
+	}
+}
+
+/// How a single day is presented in the calendar grid, as returned by the closure given to
+/// [`Calendar::decorate_days`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DayDecoration {
+	/// The day can't be selected and is greyed out.
+	pub disabled: bool,
+	/// Small dots drawn below the day number, e.g. one per event on that day.
+	pub dots: Vec<Color32>,
+	/// Short text drawn in the top right corner of the day.
+	pub badge: Option<String>,
+	/// Shown when hovering the day.
+	pub tooltip: Option<String>,
+	/// Overrides the text color of the day, including the weekend highlighting.
+	pub text_color: Option<Color32>,
+	/// Background of the day, unless it is selected.
+	pub fill: Option<Color32>,
+}
+
+impl DayDecoration {
+	pub fn disabled(mut self, disabled: bool) -> Self {
+		self.disabled = disabled;
+		self
+	}
+
+	pub fn dot(mut self, color: Color32) -> Self {
+		self.dots.push(color);
+		self
+	}
+
+	pub fn badge(mut self, badge: impl ToString) -> Self {
+		self.badge = Some(badge.to_string());
+		self
+	}
+
+	pub fn tooltip(mut self, tooltip: impl ToString) -> Self {
+		self.tooltip = Some(tooltip.to_string());
+		self
+	}
+
+	pub fn text_color(mut self, color: Color32) -> Self {
+		self.text_color = Some(color);
+		self
+	}
+
+	pub fn fill(mut self, color: Color32) -> Self {
+		self.fill = Some(color);
+		self
+	}
+}
+
+/// The times of day, which can be selected on a particular day.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct DayTimeBounds {
+	start: NaiveTime,
+	end: NaiveTime,
+}
+
+impl DayTimeBounds {
+	/// The latest time of day the editor can show.
+	const DAY_END: NaiveTime = match NaiveTime::from_hms_opt(23, 59, 59) {
+		Some(time) => time,
+		None => unreachable!(),
+	};
+
+	fn hours(&self) -> RangeInclusive<u32> {
+		self.start.hour()..=self.end.hour()
+	}
+
+	fn minutes(&self, hour: u32) -> RangeInclusive<u32> {
+		let start = if hour == self.start.hour() { self.start.minute() } else { 0 };
+		let end = if hour == self.end.hour() { self.end.minute() } else { 59 };
+		start..=end
+	}
+
+	fn seconds(&self, hour: u32, minute: u32) -> RangeInclusive<u32> {
+		let start = if (hour, minute) == (self.start.hour(), self.start.minute()) {
+			self.start.second()
+		} else {
+			0
+		};
+		let end = if (hour, minute) == (self.end.hour(), self.end.minute()) {
+			self.end.second()
+		} else {
+			59
+		};
+		start..=end
+	}
+
+	fn clamp(&self, time: NaiveTime) -> NaiveTime {
+		time.clamp(self.start, self.end)
+	}
+}
+
+/// Everything the calendar writes back to its caller.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Selection {
+	/// The selected date if any.
+	pub date: Option<NaiveDateTime>,
+	/// In range mode the end of the range.
+	pub range_end: Option<NaiveDateTime>,
+	/// In multi-date mode the selected days.
+	pub dates: Option<BTreeSet<NaiveDate>>,
+}
+
+/// Where the selected date is stored.
+enum DateSlot<'a> {
+	Required(&'a mut NaiveDateTime),
+	Optional(&'a mut Option<NaiveDateTime>),
+}
+
+/// What happened while the calendar was drawn.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct CalendarOutput {
+	/// `Enter` was pressed to confirm the selection.
+	pub confirmed: bool,
+	/// A day or week was clicked, completing a selection.
+	pub selected: bool,
+}
+
+/// What the calendar currently shows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CalendarView {
+	/// The days of a month, the default.
+	Days,
+	/// The twelve months of a year.
+	Months,
+	/// The years of a decade.
+	Years,
+}
+
+/// Default values of fields are:
+/// - locale: [`Locale::EN`]
+/// - first_weekday: taken from the locale
+/// - months: `1`
+/// - weekend_func: `date.weekday() == Weekday::Sat || date.weekday() == Weekday::Sun`
+///
+/// Clicking the month in the header switches to a grid of months, and clicking the year
+/// switches to a grid of years.
+/// Dragging the year still changes it directly.
+///
+/// While a day has keyboard focus:
+/// - arrow keys move the selection by a day or a week
+/// - `PageUp`/`PageDown` change the month, with `Shift` held they change the year
+/// - `Home`/`End` jump to the start or end of the week
+/// - in multi-date mode, `Space` adds or removes the focused day
+pub struct Calendar<'a, 'b, R>
+where
+	R: RangeBounds<NaiveDateTime>,
+{
+	id: Id,
+	slot: DateSlot<'a>,
+	// the selected date, or while nothing is selected the date whose month is shown.
+	date: NaiveDateTime,
+	has_selection: bool,
+	// the month shown while nothing is selected.
+	default_date: Option<NaiveDate>,
+	// when set, `date` is the start of a selected range ending at this date.
+	range_end: Option<&'a mut NaiveDateTime>,
+	// when set, any number of days can be selected and `date` is only the focused day.
+	multi_dates: Option<&'a mut BTreeSet<NaiveDate>>,
+	max_dates: Option<usize>,
+	pub(crate) locale: Locale,
+	// when set, overrides the locale's first day of the week.
+	first_weekday: Option<Weekday>,
+	weekend_color: Color32,
+	weekend_func: fn(&NaiveDateTime) -> bool,
+	highlight_weekend: bool,
+	show_week_numbers: bool,
+	months: u32,
+	show_seconds: bool,
+
+	// when set, the calendar will restrict dates to the given range.
+	allowed_range: Option<&'b R>,
+	// when set, only times of day in this window can be selected, on every day.
+	daily_time_window: Option<RangeInclusive<NaiveTime>>,
+	// when set, only these days can be selected.
+	allowed_dates: Option<&'b BTreeSet<NaiveDate>>,
+	day_decorator: Option<Box<dyn Fn(NaiveDate) -> DayDecoration + 'b>>,
+
+	// in a popup, navigation keys are also handled while nothing has focus.
+	pub(crate) in_popup: bool,
+
+	// set while the calendar is being drawn
+	view: CalendarView,
+	focus_selected: bool,
+	grid_focused: bool,
+	selected: bool,
+}
+
+impl<'a, 'b, R> Calendar<'a, 'b, R>
+where
+	R: RangeBounds<NaiveDateTime>,
+{
+	/// Create new calendar with unique id and mutable reference to the selected date.
+	pub fn new<T: Hash>(id: T, date: &'a mut NaiveDateTime) -> Self {
+		let current = *date;
+		Self::with_slot(Id::new(id), DateSlot::Required(date), Some(current))
+	}
+
+	/// Create new calendar with unique id and mutable reference to a date, which may be unset.
+	/// Nothing is highlighted while the date is `None`, and selecting a day sets it.
+	pub fn new_optional<T: Hash>(id: T, date: &'a mut Option<NaiveDateTime>) -> Self {
+		let current = *date;
+		Self::with_slot(Id::new(id), DateSlot::Optional(date), current)
+	}
+
+	fn with_slot(id: Id, slot: DateSlot<'a>, current: Option<NaiveDateTime>) -> Self {
+		Self {
+			id,
+			slot,
+			date: current.unwrap_or_else(|| Local::now().date_naive().and_time(NaiveTime::MIN)),
+			has_selection: current.is_some(),
+			default_date: None,
+			range_end: None,
+			multi_dates: None,
+			max_dates: None,
+			locale: Locale::default(),
+			first_weekday: None,
+			weekend_color: Color32::from_rgb(196, 0, 0),
+			weekend_func: |date| date.weekday() == Weekday::Sat || date.weekday() == Weekday::Sun,
+			highlight_weekend: true,
+			show_week_numbers: false,
+			months: 1,
+			show_seconds: false,
+			allowed_range: None,
+			daily_time_window: None,
+			allowed_dates: None,
+			day_decorator: None,
+			in_popup: false,
+			view: CalendarView::Days,
+			focus_selected: false,
+			grid_focused: false,
+			selected: false,
+		}
+	}
+
+	/// Show the month of this date while no date is selected.
+	/// Default is the current month
+	#[must_use]
+	pub fn default_date(mut self, date: NaiveDate) -> Self {
+		self.default_date = Some(date);
+		self
+	}
+
+	/// Select a range of dates instead of a single one. The date passed to [`Self::new`] is
+	/// the start of the range and `end` is its end.
+	///
+	/// The first click in the calendar starts a new range and the second one finishes it.
+	#[must_use]
+	pub fn range_end(mut self, end: &'a mut NaiveDateTime) -> Self {
+		self.range_end = Some(end);
+		self
+	}
+
+	/// Select any number of individual days instead of a single date. The date passed to
+	/// [`Self::new`] is only the day the keyboard focus starts on and Shift-click ranges start from.
+	///
+	/// - click selects only the clicked day
+	/// - Ctrl-click adds the clicked day to the selection or removes it
+	/// - Shift-click selects all days from the previously clicked day to the clicked one,
+	///   with Ctrl held as well they are added to the selection
+	///
+	/// Clicking a week number selects the days of that week in the same way.
+	/// This can't be combined with [`Self::range_end`].
+	#[must_use]
+	pub fn multi_select(mut self, dates: &'a mut BTreeSet<NaiveDate>) -> Self {
+		self.multi_dates = Some(dates);
+		self
+	}
+
+	/// In multi-date mode, don't allow selecting more than this many days.
+	/// Clicks which would exceed it only add days as long as there is room.
+	#[must_use]
+	pub fn max_selected(mut self, max: usize) -> Self {
+		self.max_dates = Some(max);
+		self
+	}
+
+	/// Set the language of month and weekday names and the first day of the week.
+	#[must_use]
+	pub fn locale(mut self, locale: Locale) -> Self {
+		self.locale = locale;
+		self
+	}
+
+	/// If flag is set to true then first day in calendar will be sunday otherwise monday.
+	/// Default is taken from the locale
+	#[must_use]
+	pub fn sunday_first(self, flag: bool) -> Self {
+		self.first_weekday(if flag { Weekday::Sun } else { Weekday::Mon })
+	}
+
+	/// Set the first day in calendar, overriding the one of the locale.
+	#[must_use]
+	pub fn first_weekday(mut self, weekday: Weekday) -> Self {
+		self.first_weekday = Some(weekday);
+		self
+	}
+
+	///If highlight is true then weekends text color will be `weekend_color` instead default text
+	///color.
+	#[must_use]
+	pub fn highlight_weekend(mut self, highlight: bool) -> Self {
+		self.highlight_weekend = highlight;
+		self
+	}
+
+	///Set weekends highlighting color.
+	#[must_use]
+	pub fn highlight_weekend_color(mut self, color: Color32) -> Self {
+		self.weekend_color = color;
+		self
+	}
+
+	/// Show this many consecutive months side by side.
+	///
+	/// The shown months only scroll once the selected date leaves them, so that clicking a day
+	/// in a later month doesn't move it.
+	/// Default is 1
+	#[must_use]
+	pub fn months(mut self, months: u32) -> Self {
+		self.months = months.max(1);
+		self
+	}
+
+	/// If flag is set to true then the calendar will have a leading column with ISO week numbers.
+	/// Clicking a week number is like clicking the first day of that week that can be selected,
+	/// or in multi-date mode selects all days of the week that can be selected.
+	/// Default is false
+	#[must_use]
+	pub fn show_week_numbers(mut self, flag: bool) -> Self {
+		self.show_week_numbers = flag;
+		self
+	}
+
+	/// Set function, which will decide if date is a weekend day or not.
+	pub fn weekend_days(mut self, is_weekend: fn(&NaiveDateTime) -> bool) -> Self {
+		self.weekend_func = is_weekend;
+		self
+	}
+
+	/// The calendar will restrict dates to the given range.
+	pub fn restrict_range(mut self, allowed_range: &'b R) -> Self {
+		self.allowed_range = Some(allowed_range);
+		self
+	}
+
+	/// The calendar will only allow selecting times of day between `start` and `end`, on every
+	/// day, e.g. business hours from 09:00 to 17:00.
+	/// This can be combined with [`Self::restrict_range`], days on which the two don't overlap
+	/// can't be selected.
+	///
+	/// `start` must not be after `end`: windows past midnight, like 22:00 to 06:00, are not
+	/// supported.
+	pub fn daily_time_window(mut self, start: NaiveTime, end: NaiveTime) -> Self {
+		debug_assert!(start <= end, "daily time window from {start} to {end} passes midnight");
+		self.daily_time_window = Some(start..=end);
+		self
+	}
+
+	/// If flag is set to true then seconds can be edited along with hours and minutes.
+	/// Default is false
+	#[must_use]
+	pub fn show_seconds(mut self, flag: bool) -> Self {
+		self.show_seconds = flag;
+		self
+	}
+
+	/// The calendar will only allow selecting days in the given set.
+	/// This can be combined with [`Self::restrict_range`].
+	pub fn restrict_dates(mut self, allowed_dates: &'b BTreeSet<NaiveDate>) -> Self {
+		self.allowed_dates = Some(allowed_dates);
+		self
+	}
+
+	/// Set a closure, which decides how each day in the calendar is presented and whether it can
+	/// be selected at all.
+	///
+	/// ```no_run
+	/// # use chrono::prelude::*;
+	/// # use eframe::egui::Color32;
+	/// # use grus_gui_lib::calendar::{Calendar, DayDecoration};
+	/// # let mut date = NaiveDateTime::default();
+	/// # let fully_booked = |_: NaiveDate| false;
+	/// # let event_count = |_: NaiveDate| 0;
+	/// Calendar::<std::ops::RangeFull>::new("booking", &mut date).decorate_days(|day| {
+	///     let mut decoration = DayDecoration::default().disabled(fully_booked(day));
+	///     for _ in 0..event_count(day) {
+	///         decoration = decoration.dot(Color32::LIGHT_BLUE);
+	///     }
+	///     decoration
+	/// });
+	/// ```
+	pub fn decorate_days(mut self, decorator: impl Fn(NaiveDate) -> DayDecoration + 'b) -> Self {
+		self.day_decorator = Some(Box::new(decorator));
+		self
+	}
+
+	fn week_start(&self) -> Weekday {
+		self.first_weekday.unwrap_or(self.locale.first_weekday)
+	}
+
+	/// Draw names of week days as 7 columns of grid without calling `Ui::end_row`
+	fn show_grid_header(&mut self, ui: &mut Ui) {
+		if self.show_week_numbers {
+			ui.label(RichText::new(self.locale.week_label).weak());
+		}
+		let mut weekday = self.week_start();
+		for _ in 0..7 {
+			ui.label(self.locale.weekday_name(weekday));
+			weekday = weekday.succ();
+		}
+	}
+
+	/// Get number of days between first day of the month and the first day of the week
+	fn get_start_offset_of_calendar(&self, first_day: &NaiveDateTime) -> u32 {
+		days_since_week_start(first_day.weekday(), self.week_start())
+	}
+
+	/// Get number of days between first day of the next month and the first day of the week
+	fn get_end_offset_of_calendar(&self, first_day: &NaiveDateTime) -> u32 {
+		(7 - days_since_week_start(first_day.weekday(), self.week_start())) % 7
+	}
+
+	fn show_calendar_grid(&mut self, ui: &mut Ui) {
+		match self.view {
+			CalendarView::Days => self.show_day_grids(ui),
+			CalendarView::Months => self.show_month_grid(ui),
+			CalendarView::Years => self.show_year_grid(ui),
+		}
+	}
+
+	fn set_view(&mut self, ui: &Ui, view: CalendarView) {
+		self.view = view;
+		ui.data_mut(|d| d.insert_temp(self.id.with("view"), view));
+	}
+
+	/// Draw the twelve months of the current year as a 4x3 grid.
+	fn show_month_grid(&mut self, ui: &mut Ui) {
+		egui::Grid::new(self.id.with("months")).min_col_width(60.0).show(ui, |ui| {
+			for month in 1..=12 {
+				let first_day = NaiveDate::from_ymd_opt(self.date.year(), month, 1);
+				let last_day = last_day_of_month(self.date.year(), month);
+				let is_enabled = match (first_day, last_day) {
+					(Some(first_day), Some(last_day)) => self.overlaps_range(first_day, last_day),
+					_ => false,
+				};
+
+				let mut button = egui::Button::new(self.locale.short_month_name(month));
+				if month == self.date.month() {
+					button = button.fill(ui.style().visuals.selection.bg_fill);
+				}
+				let clicked = ui
+					.centered_and_justified(|ui| ui.add_enabled(is_enabled, button).clicked())
+					.inner;
+				if clicked {
+					self.date = self.clamp_to_range(with_year_month(self.date, self.date.year(), month));
+					self.set_view(ui, CalendarView::Days);
+				}
+				if month % 3 == 0 {
+					ui.end_row();
+				}
+			}
+		});
+	}
+
+	/// Draw the years of the current decade as a 4x3 grid, including the last year of the
+	/// previous decade and the first one of the next.
+	fn show_year_grid(&mut self, ui: &mut Ui) {
+		let decade_start = self.date.year().div_euclid(10) * 10;
+		egui::Grid::new(self.id.with("years")).min_col_width(60.0).show(ui, |ui| {
+			for (i, year) in (decade_start - 1..=decade_start + 10).enumerate() {
+				let first_day = NaiveDate::from_ymd_opt(year, 1, 1);
+				let last_day = NaiveDate::from_ymd_opt(year, 12, 31);
+				let is_enabled = match (first_day, last_day) {
+					(Some(first_day), Some(last_day)) => self.overlaps_range(first_day, last_day),
+					_ => false,
+				};
+
+				let mut button = egui::Button::new(year.to_string());
+				if year == self.date.year() {
+					button = button.fill(ui.style().visuals.selection.bg_fill);
+				} else if year.div_euclid(10) * 10 != decade_start {
+					button = button.frame(false);
+				}
+				let clicked = ui
+					.centered_and_justified(|ui| ui.add_enabled(is_enabled, button).clicked())
+					.inner;
+				if clicked {
+					self.date = self.clamp_to_range(with_year_month(self.date, year, self.date.month()));
+					self.set_view(ui, CalendarView::Months);
+				}
+				if i % 3 == 2 {
+					ui.end_row();
+				}
+			}
+		});
+	}
+
+	/// Does any part of the days from `first_day` up to and including `last_day` lie in
+	/// `allowed_range`?
+	fn overlaps_range(&self, first_day: NaiveDate, last_day: NaiveDate) -> bool {
+		let Some(range) = self.allowed_range else {
+			return true;
+		};
+		let start = first_day.and_time(NaiveTime::MIN);
+		let end = last_day.and_time(DayTimeBounds::DAY_END);
+		let starts_before_range_end = match range.end_bound() {
+			Bound::Included(upper_b) => start <= *upper_b,
+			Bound::Excluded(upper_b) => start < *upper_b,
+			Bound::Unbounded => true,
+		};
+		let ends_after_range_start = match range.start_bound() {
+			Bound::Included(lower_b) => *lower_b <= end,
+			Bound::Excluded(lower_b) => *lower_b < end,
+			Bound::Unbounded => true,
+		};
+		starts_before_range_end && ends_after_range_start
+	}
+
+	/// First day of the first month shown, moved just far enough for the selected date to be
+	/// visible.
+	fn first_shown_month(&self, ui: &Ui) -> NaiveDateTime {
+		let selected_month = self.date.with_day(1).unwrap();
+		let id = self.id.with("first_shown_month");
+		let first = match ui.data(|d| d.get_temp::<NaiveDate>(id)) {
+			// keep the time of day of the selected date for the days shown
+			Some(first) => first.and_time(self.date.time()),
+			None => selected_month,
+		};
+		let first = if selected_month < first {
+			selected_month
+		} else if add_months(first, self.months as i32) <= selected_month {
+			add_months(selected_month, 1 - self.months as i32)
+		} else {
+			first
+		};
+		ui.data_mut(|d| d.insert_temp(id, first.date()));
+		first
+	}
+
+	/// Draw the grids of days of all shown months next to each other.
+	fn show_day_grids(&mut self, ui: &mut Ui) {
+		if self.months == 1 {
+			let first_day_of_month = self.date.with_day(1).unwrap();
+			self.show_day_grid(ui, 0, first_day_of_month);
+			return;
+		}
+		let first_month = self.first_shown_month(ui);
+		ui.horizontal_top(|ui| {
+			for i in 0..self.months {
+				let first_day_of_month = add_months(first_month, i as i32);
+				ui.vertical(|ui| {
+					ui.vertical_centered(|ui| {
+						ui.strong(format!(
+							"{} {}",
+							self.locale.month_name(first_day_of_month.month()),
+							first_day_of_month.year(),
+						));
+					});
+					self.show_day_grid(ui, i, first_day_of_month);
+				});
+			}
+		});
+	}
+
+	fn show_day_grid(&mut self, ui: &mut Ui, index: u32, first_day_of_current_month: NaiveDateTime) {
+		egui::Grid::new(self.id.with(("calendar", index))).min_col_width(30.0).show(ui, |ui| {
+			self.show_grid_header(ui);
+			let start_offset = self.get_start_offset_of_calendar(&first_day_of_current_month);
+			let days_in_month = get_days_from_month(
+				first_day_of_current_month.year(),
+				first_day_of_current_month.month(),
+			);
+			let first_day_of_next_month =
+				first_day_of_current_month + Duration::days(days_in_month);
+			let end_offset = self.get_end_offset_of_calendar(&first_day_of_next_month);
+			let start_date = first_day_of_current_month - Duration::days(start_offset.into());
+			for i in 0..(start_offset as i64 + days_in_month + end_offset as i64) {
+				if i % 7 == 0 {
+					ui.end_row();
+					if self.show_week_numbers {
+						self.show_week_button(start_date + Duration::days(i), ui);
+					}
+				}
+				let d = start_date + Duration::days(i);
+				self.show_day_button(d, first_day_of_current_month.month(), ui);
+			}
+		});
+	}
+
+	/// Draw the ISO week number of the calendar row starting with `row_start`.
+	fn show_week_button(&mut self, row_start: NaiveDateTime, ui: &mut Ui) {
+		// a row not starting on monday spans two ISO weeks, like ISO weeks we go by its thursday
+		let thursday = row_start
+			+ Duration::days(days_since_week_start(Weekday::Thu, row_start.weekday()).into());
+		let week = thursday.iso_week().week();
+		let button = egui::Button::new(RichText::new(week.to_string()).weak()).frame(false);
+		if ui.add(button).clicked() {
+			if self.multi_dates.is_some() {
+				let modifiers = ui.input(|i| i.modifiers);
+				self.select_days(row_start.date(), (row_start + Duration::days(6)).date(), modifiers);
+				return;
+			}
+			// otherwise like a click on the first day of the week that can be selected
+			let first_enabled = row_start.date().iter_days().take(7).find(|day| self.is_day_enabled(*day));
+			if let Some(day) = first_enabled {
+				self.select_day(day.and_time(row_start.time()), ui);
+			}
+		}
+	}
+
+	/// In range mode, is the next click going to finish the range?
+	fn picking_range_end(&self, ui: &Ui) -> bool {
+		ui.data(|d| d.get_temp(self.id.with("picking_range_end")).unwrap_or(false))
+	}
+
+	fn set_picking_range_end(&self, ui: &Ui, picking: bool) {
+		ui.data_mut(|d| d.insert_temp(self.id.with("picking_range_end"), picking));
+	}
+
+	/// Select the clicked day, or in range mode, start or finish the range.
+	fn select_day(&mut self, date: NaiveDateTime, ui: &Ui) {
+		let date = self.clamp_to_range(date);
+		if self.multi_dates.is_some() {
+			// a focused day is also clicked with `Space` and `Enter`, the latter confirms instead
+			let (modifiers, space, enter) =
+				ui.input(|i| (i.modifiers, i.key_pressed(Key::Space), i.key_pressed(Key::Enter)));
+			if !enter {
+				let modifiers = if space { Modifiers::COMMAND } else { modifiers };
+				self.select_days(date.date(), date.date(), modifiers);
+			}
+			return;
+		}
+		let picking_end = self.picking_range_end(ui) && self.has_selection;
+		self.selected = self.range_end.is_none() || picking_end;
+		self.has_selection = true;
+		match &mut self.range_end {
+			None => self.date = date,
+			Some(end) => {
+				let date_as_end = date.date().and_time(end.time());
+				if !picking_end {
+					self.date = date;
+					**end = date_as_end;
+				} else if date < self.date {
+					**end = self.date.date().and_time(end.time());
+					self.date = date;
+				} else {
+					**end = date_as_end;
+				}
+				self.set_picking_range_end(ui, !picking_end);
+			}
+		}
+	}
+
+	/// In multi-date mode, change the selected days after a click on the days from `first` to
+	/// `last`, according to the held modifiers.
+	fn select_days(&mut self, first: NaiveDate, last: NaiveDate, modifiers: Modifiers) {
+		let anchor = self.date.date();
+		let (start, end) = if modifiers.shift && self.has_selection {
+			(first.min(anchor), last.max(anchor))
+		} else {
+			(first, last)
+		};
+		let days: Vec<NaiveDate> = start
+			.iter_days()
+			.take_while(|day| *day <= end)
+			.filter(|day| self.is_day_enabled(*day))
+			.collect();
+		let max_dates = self.max_dates;
+		let has_room = |dates: &BTreeSet<NaiveDate>| max_dates.map_or(true, |max| dates.len() < max);
+		let Some(dates) = &mut self.multi_dates else {
+			return;
+		};
+
+		if modifiers.command && !modifiers.shift && start == end {
+			if !dates.remove(&start) && has_room(dates) {
+				dates.insert(start);
+			}
+		} else {
+			if !modifiers.command {
+				dates.clear();
+			}
+			for day in days {
+				if !has_room(dates) {
+					break;
+				}
+				dates.insert(day);
+			}
+		}
+		if !modifiers.shift || !self.has_selection {
+			self.date = first.and_time(self.date.time());
+		}
+		self.has_selection = true;
+	}
+
+	fn decoration(&self, date: NaiveDate) -> DayDecoration {
+		match &self.day_decorator {
+			Some(decorator) => decorator(date),
+			None => DayDecoration::default(),
+		}
+	}
+
+	/// Can the day be selected, ignoring its decoration?
+	fn is_day_allowed(&self, date: NaiveDate) -> bool {
+		let mut is_allowed = self.time_bounds(date).is_some();
+
+		if let Some(allowed_dates) = self.allowed_dates {
+			is_allowed &= allowed_dates.contains(&date);
+		}
+		is_allowed
+	}
+
+	fn is_day_enabled(&self, date: NaiveDate) -> bool {
+		self.is_day_allowed(date) && !self.decoration(date).disabled
+	}
+
+	fn show_day_button(&mut self, date: NaiveDateTime, month: u32, ui: &mut Ui) {
+		let decoration = self.decoration(date.date());
+		let is_enabled = self.is_day_enabled(date.date());
+
+		ui.centered_and_justified(|ui| {
+			let mut button = egui::Button::new(date.day().to_string());
+
+			let range_end = self.range_end.as_deref().map(NaiveDateTime::date);
+			let is_range_end = range_end == Some(date.date());
+			let is_inside_range = self.has_selection
+				&& range_end.is_some_and(|end| self.date.date() < date.date() && date.date() < end);
+			let is_selected = match &self.multi_dates {
+				Some(dates) => dates.contains(&date.date()),
+				None => self.has_selection && (self.date == date || is_range_end),
+			};
+			let is_in_month = date.month() == month;
+			if !is_in_month {
+				button = button.frame(false);
+			} else if is_selected {
+				// if the date is the selected date,
+				// give the button an fill with the 'selection style'
+				button = button.fill(ui.style().visuals.selection.bg_fill);
+			} else if is_inside_range {
+				button = button.fill(ui.style().visuals.selection.bg_fill.linear_multiply(0.4));
+			} else if let Some(fill) = decoration.fill {
+				button = button.fill(fill);
+			}
+			if let Some(color) = decoration.text_color {
+				ui.style_mut().visuals.override_text_color = Some(color);
+			} else if self.highlight_weekend && (self.weekend_func)(&date) {
+				ui.style_mut().visuals.override_text_color = Some(self.weekend_color);
+			}
+			let mut response = ui.add_enabled(is_enabled, button);
+			paint_day_decoration(ui, response.rect, &decoration);
+			if let Some(tooltip) = decoration.tooltip {
+				response = response
+					.on_hover_text(tooltip.clone())
+					.on_disabled_hover_text(tooltip);
+			}
+			if self.date == date && is_in_month && self.focus_selected {
+				response.request_focus();
+				self.focus_selected = false;
+			}
+			self.grid_focused |= response.has_focus();
+			if response.clicked() {
+				self.select_day(date, ui);
+			}
+		});
+	}
+
+	/// Move the selection according to the navigation keys pressed this frame.
+	/// Returns `true` if `Enter` was pressed to confirm the selection.
+	fn handle_keyboard(&mut self, ui: &mut Ui) -> bool {
+		let old_date = self.date;
+		let week_start = self.get_start_offset_of_calendar(&old_date) as i64;
+		let (new_date, confirm) = ui.input_mut(|i| {
+			let mut date = old_date;
+			let day_steps = [
+				(Key::ArrowLeft, Duration::days(-1)),
+				(Key::ArrowRight, Duration::days(1)),
+				(Key::ArrowUp, Duration::days(-7)),
+				(Key::ArrowDown, Duration::days(7)),
+				(Key::Home, Duration::days(-week_start)),
+				(Key::End, Duration::days(6 - week_start)),
+			];
+			for (key, step) in day_steps {
+				for _ in 0..i.count_and_consume_key(Modifiers::NONE, key) {
+					date += step;
+				}
+			}
+			let month_steps = [
+				(Modifiers::NONE, Key::PageUp, -1),
+				(Modifiers::NONE, Key::PageDown, 1),
+				(Modifiers::SHIFT, Key::PageUp, -12),
+				(Modifiers::SHIFT, Key::PageDown, 12),
+			];
+			for (modifiers, key, months) in month_steps {
+				for _ in 0..i.count_and_consume_key(modifiers, key) {
+					date = add_months(date, months);
+				}
+			}
+			(date, i.consume_key(Modifiers::NONE, Key::Enter))
+		});
+
+		if new_date != old_date {
+			// skip over days which can't be selected, in the direction of movement
+			let step = if new_date < old_date { Duration::days(-1) } else { Duration::days(1) };
+			let mut date = self.clamp_to_range(new_date);
+			for _ in 0..366 {
+				if self.is_day_enabled(date.date()) {
+					self.date = date;
+					self.has_selection = true;
+					ui.data_mut(|d| d.insert_temp(self.id.with("focus_selected"), true));
+					break;
+				}
+				date = self.clamp_to_range(date + step);
+			}
+		}
+		// without a selection, `Enter` selects the focused day
+		if confirm && !self.has_selection {
+			self.has_selection = self.is_day_enabled(self.date.date());
+		}
+		confirm && self.has_selection
+	}
+
+	/// The times of day that can be selected on `date`, taking both `allowed_range` and
+	/// `daily_time_window` into account.
+	/// Returns `None` if no time on that day can be selected.
+	fn time_bounds(&self, date: NaiveDate) -> Option<DayTimeBounds> {
+		let (start, end) = match &self.daily_time_window {
+			Some(window) => (*window.start(), *window.end()),
+			None => (NaiveTime::MIN, DayTimeBounds::DAY_END),
+		};
+		let mut start = date.and_time(start);
+		let mut end = date.and_time(end);
+
+		if let Some(range) = self.allowed_range {
+			// the time editor has a resolution of seconds
+			match range.start_bound() {
+				Bound::Included(lower_b) => start = start.max(*lower_b),
+				Bound::Excluded(lower_b) => start = start.max(*lower_b + Duration::seconds(1)),
+				Bound::Unbounded => {}
+			}
+			match range.end_bound() {
+				Bound::Included(upper_b) => end = end.min(*upper_b),
+				Bound::Excluded(upper_b) => end = end.min(*upper_b - Duration::seconds(1)),
+				Bound::Unbounded => {}
+			}
+		}
+
+		(start <= end).then(|| DayTimeBounds { start: start.time(), end: end.time() })
+	}
+
+	/// Move `date` to the nearest date inside of `allowed_range`, and its time into the times
+	/// that can be selected on that day.
+	fn clamp_to_range(&self, date: NaiveDateTime) -> NaiveDateTime {
+		let date = self.clamp_to_allowed_range(date);
+		match self.time_bounds(date.date()) {
+			Some(bounds) => date.date().and_time(bounds.clamp(date.time())),
+			None => date,
+		}
+	}
+
+	fn clamp_to_allowed_range(&self, date: NaiveDateTime) -> NaiveDateTime {
+		match self.allowed_range {
+			None => date,
+			// Ensure the bound is never left.
+			Some(range) => match (range.start_bound(), range.end_bound()) {
+				(Bound::Included(lower_b), _) if date < *lower_b => *lower_b,
+				(Bound::Excluded(lower_b), _) if date <= *lower_b
+					=> *lower_b + Duration::nanoseconds(1),
+
+				(_, Bound::Included(upper_b)) if *upper_b < date => *upper_b,
+				(_, Bound::Excluded(upper_b)) if *upper_b <= date
+					=> *upper_b - Duration::nanoseconds(1),
+
+				_ => date,
+			}
+		}
+	}
+
+	/// Draw drag values for the time of day, limited to the times that can be selected on the
+	/// selected day.
+	fn show_time_editor(&mut self, ui: &mut Ui) {
+		let bounds = self.time_bounds(self.date.date());
+		let time_bounds = bounds.unwrap_or(DayTimeBounds { start: NaiveTime::MIN, end: DayTimeBounds::DAY_END });
+
+		let time = self.date.time();
+		let (mut hour, mut minute, mut second) = (time.hour(), time.minute(), time.second());
+
+		ui.add_enabled_ui(self.has_selection && bounds.is_some(), |ui| {
+			ui.add(DragValue::new(&mut hour).clamp_range(time_bounds.hours()));
+			ui.label(":");
+			ui.add(DragValue::new(&mut minute).clamp_range(time_bounds.minutes(hour)));
+			if self.show_seconds {
+				ui.label(":");
+				ui.add(DragValue::new(&mut second).clamp_range(time_bounds.seconds(hour, minute)));
+			}
+		});
+
+		if (hour, minute, second) != (time.hour(), time.minute(), time.second()) {
+			// changing the hour can leave minutes and seconds out of bounds
+			let new_time = NaiveTime::from_hms_opt(hour, minute, second).unwrap_or(time);
+			self.date = self.date.date().and_time(time_bounds.clamp(new_time));
+		}
+	}
+
+	/// Draw the header and the grid, and handle navigation keys.
+	pub(crate) fn show_contents(&mut self, ui: &mut Ui) -> CalendarOutput {
+		self.selected = false;
+		self.load_empty_date(ui);
+		let focus_id = self.id.with("focus_selected");
+		self.focus_selected = ui.data_mut(|d| {
+			let focus = d.get_temp(focus_id).unwrap_or(false);
+			d.remove::<bool>(focus_id);
+			focus
+		});
+		self.view = ui.data(|d| d.get_temp(self.id.with("view"))).unwrap_or(CalendarView::Days);
+
+		self.show_header(ui);
+		self.show_calendar_grid(ui);
+
+		// don't steal keys from e.g. the time editor while it has focus
+		let is_days_view = self.view == CalendarView::Days;
+		let has_focus = self.grid_focused || self.in_popup && ui.memory(|m| m.focus().is_none());
+		let confirmed = is_days_view && has_focus && self.handle_keyboard(ui);
+		self.store(ui);
+		CalendarOutput { confirmed, selected: self.selected }
+	}
+
+	/// While nothing is selected, show the month left open last time, or the default one.
+	fn load_empty_date(&mut self, ui: &Ui) {
+		if self.has_selection {
+			return;
+		}
+		let default_date = self.default_date.map(|date| date.and_time(NaiveTime::MIN));
+		if let Some(date) = ui.data(|d| d.get_temp(self.id.with("empty_date"))).or(default_date) {
+			self.date = date;
+		}
+	}
+
+	/// Write the working date back to the caller's date.
+	fn store(&mut self, ui: &Ui) {
+		if !self.has_selection {
+			ui.data_mut(|d| d.insert_temp(self.id.with("empty_date"), self.date));
+		}
+		self.write_slot();
+	}
+
+	fn write_slot(&mut self) {
+		match &mut self.slot {
+			DateSlot::Required(date) => **date = self.date,
+			DateSlot::Optional(date) => **date = self.has_selection.then_some(self.date),
+		}
+	}
+
+	pub(crate) fn selection(&self) -> Selection {
+		Selection {
+			date: self.has_selection.then_some(self.date),
+			range_end: self.range_end.as_deref().copied(),
+			dates: self.multi_dates.as_deref().cloned(),
+		}
+	}
+
+	pub(crate) fn set_selection(&mut self, Selection { date, range_end, dates }: Selection) {
+		self.has_selection = date.is_some() || !self.is_optional();
+		if let Some(date) = date {
+			self.date = date;
+		}
+		if let (Some(end), Some(new_end)) = (&mut self.range_end, range_end) {
+			**end = new_end;
+		}
+		if let (Some(dates), Some(new_dates)) = (&mut self.multi_dates, dates) {
+			**dates = new_dates;
+		}
+		self.write_slot();
+	}
+
+	pub(crate) fn is_optional(&self) -> bool {
+		matches!(self.slot, DateSlot::Optional(_))
+	}
+
+	/// Can the selection be cleared at all?
+	pub(crate) fn is_clearable(&self) -> bool {
+		self.is_optional() || self.multi_dates.is_some()
+	}
+
+	/// Is there a selection, which [`Self::clear`] would remove?
+	pub(crate) fn can_clear(&self) -> bool {
+		self.is_optional() && self.has_selection
+			|| self.multi_dates.as_ref().is_some_and(|dates| !dates.is_empty())
+	}
+
+	/// Unset the date of a calendar created with [`Self::new_optional`], and in multi-date mode
+	/// deselect all days.
+	pub(crate) fn clear(&mut self, ui: &Ui) {
+		if let Some(dates) = &mut self.multi_dates {
+			dates.clear();
+		}
+		if self.is_optional() {
+			self.has_selection = false;
+			self.set_picking_range_end(ui, false);
+			self.store(ui);
+		}
+	}
+
+	/// Select today, keeping the selected time of day where possible.
+	pub(crate) fn select_today(&mut self, ui: &Ui) {
+		let today = self.clamp_to_range(Local::now().date_naive().and_time(self.date.time()));
+		self.date = today;
+		self.has_selection = true;
+		if let Some(end) = &mut self.range_end {
+			**end = today.date().and_time(end.time());
+		}
+		self.set_picking_range_end(ui, false);
+		self.write_slot();
+		ui.data_mut(|d| d.insert_temp(self.id.with("focus_selected"), true));
+	}
+
+	/// Show the grid of days, with keyboard focus on the selected day, next time the calendar
+	/// is drawn.
+	pub(crate) fn reset(&self, ui: &Ui) {
+		ui.data_mut(|d| {
+			d.insert_temp(self.id.with("focus_selected"), true);
+			d.insert_temp(self.id.with("view"), CalendarView::Days);
+		});
+	}
+
+	/// Draw current month and buttons for next and previous month.
+	fn show_header(&mut self, ui: &mut Ui) {
+		ui.horizontal(|ui| {
+			// selected days in multi-date mode have no time
+			if self.multi_dates.is_none() {
+				self.show_time_editor(ui);
+			}
+			match self.view {
+				CalendarView::Days => {
+					self.show_month_control(ui);
+					self.show_year_control(ui);
+				}
+				CalendarView::Months => self.show_year_control(ui),
+				CalendarView::Years => self.show_decade_control(ui),
+			}
+		});
+	}
+
+	/// Draw button with text and add duration to current date when that button is clicked.
+	fn date_step_button(&mut self, ui: &mut Ui, text: impl ToString, duration: Duration) {
+		let new_date = self.date + duration;
+
+		if ui.button(text.to_string()).clicked() {
+			self.date = self.clamp_to_range(new_date);
+		}
+	}
+
+	/// Draw drag value widget with current year and two buttons which substract and add 365 days
+	/// to current date.
+	fn show_year_control(&mut self, ui: &mut Ui) {
+		self.date_step_button(ui, "⬅", Duration::days(-365));
+
+		let mut drag_year = self.date.year();
+		let year_response = ui.add(DragValue::new(&mut drag_year).clamp_range(self.year_bounds()));
+		if drag_year != self.date.year() {
+			self.date = self.clamp_to_range(with_year_month(self.date, drag_year, self.date.month()));
+		}
+		// clicking the year, instead of dragging it, opens the grid of years
+		if year_response.clicked() {
+			year_response.surrender_focus();
+			self.set_view(ui, CalendarView::Years);
+		}
+
+		self.date_step_button(ui, "➡", Duration::days(365));
+	}
+
+	/// The years covered by `allowed_range`.
+	fn year_bounds(&self) -> RangeInclusive<i32> {
+		let (mut start, mut end) = (NaiveDate::MIN.year(), NaiveDate::MAX.year());
+		if let Some(range) = self.allowed_range {
+			match range.start_bound() {
+				Bound::Included(lower_b) | Bound::Excluded(lower_b) => start = lower_b.year(),
+				Bound::Unbounded => {}
+			}
+			match range.end_bound() {
+				Bound::Included(upper_b) => end = upper_b.year(),
+				Bound::Excluded(upper_b) => end = (*upper_b - Duration::nanoseconds(1)).year(),
+				Bound::Unbounded => {}
+			}
+		}
+		start..=end.max(start)
+	}
+
+	/// The months of `year` covered by `allowed_range`.
+	fn month_bounds(&self, year: i32) -> RangeInclusive<u32> {
+		let mut months = (1..=12).filter(|&month| {
+			let first_day = NaiveDate::from_ymd_opt(year, month, 1);
+			match (first_day, last_day_of_month(year, month)) {
+				(Some(first_day), Some(last_day)) => self.overlaps_range(first_day, last_day),
+				_ => false,
+			}
+		});
+		let start = months.next().unwrap_or(1);
+		let end = months.next_back().unwrap_or(start);
+		start..=end
+	}
+
+	/// Draw the current decade and two buttons which substract and add ten years to current date.
+	fn show_decade_control(&mut self, ui: &mut Ui) {
+		let step = |date: NaiveDateTime, years: i32| add_months(date, 12 * years);
+		if ui.button("⬅").clicked() {
+			self.date = self.clamp_to_range(step(self.date, -10));
+		}
+		let decade_start = self.date.year().div_euclid(10) * 10;
+		ui.label(format!("{} – {}", decade_start, decade_start + 9));
+		if ui.button("➡").clicked() {
+			self.date = self.clamp_to_range(step(self.date, 10));
+		}
+	}
+
+	/// Draw drag value widget with current month and two buttons which substract and add 30 days
+	/// to current date.
+	fn show_month_control(&mut self, ui: &mut Ui) {
+		self.date_step_button(ui, "⬅", Duration::days(-30));
+
+		let locale = self.locale;
+		let mut drag_month = self.date.month();
+		let month_drag_value = DragValue::new(&mut drag_month)
+			.clamp_range(self.month_bounds(self.date.year()))
+			.speed(0.05)
+			.custom_formatter(|month, _| locale.short_month_name(month as u32).to_string())
+			.custom_parser(|text| locale.parse_month(text).map(f64::from));
+		let month_response = ui.add(month_drag_value);
+		if drag_month != self.date.month() {
+			self.date = self.clamp_to_range(with_year_month(self.date, self.date.year(), drag_month));
+		}
+		// clicking the month, instead of dragging it, opens the grid of months
+		if month_response.clicked() {
+			month_response.surrender_focus();
+			self.set_view(ui, CalendarView::Months);
+		}
+
+		self.date_step_button(ui, "➡", Duration::days(30));
+	}
+}
+
+impl<'a, 'b, R> Widget for Calendar<'a, 'b, R>
+where
+	R: RangeBounds<NaiveDateTime>,
+{
+	fn ui(mut self, ui: &mut Ui) -> Response {
+		let old_selection = self.selection();
+		let mut response = ui.vertical(|ui| self.show_contents(ui)).response;
+		if self.selection() != old_selection {
+			response.mark_changed();
+		}
+		response
+	}
+}
+
+/// Draw the dots and badge of a day on top of its button.
+fn paint_day_decoration(ui: &Ui, rect: egui::Rect, decoration: &DayDecoration) {
+	let painter = ui.painter();
+	let radius = 1.5;
+	let spacing = 2.0 * radius + 1.0;
+	let dots_width = decoration.dots.len() as f32 * spacing - 1.0;
+	let mut center = pos2(rect.center().x - 0.5 * dots_width + radius, rect.bottom() - 2.0 * radius);
+	for color in &decoration.dots {
+		painter.circle_filled(center, radius, *color);
+		center.x += spacing;
+	}
+
+	if let Some(badge) = &decoration.badge {
+		painter.text(
+			rect.right_top(),
+			Align2::RIGHT_TOP,
+			badge,
+			FontId::proportional(8.0),
+			ui.visuals().strong_text_color(),
+		);
+	}
+}
+
+/// Number of days from `week_start` forward to `weekday`.
+pub(crate) fn days_since_week_start(weekday: Weekday, week_start: Weekday) -> u32 {
+	(weekday.num_days_from_monday() + 7 - week_start.num_days_from_monday()) % 7
+}
+
+/// Move `date` to the given year and month, keeping the day as far as the month is long.
+fn with_year_month(date: NaiveDateTime, year: i32, month: u32) -> NaiveDateTime {
+	let day = date.day().min(get_days_from_month(year, month) as u32);
+	NaiveDate::from_ymd_opt(year, month, day)
+		.map_or(date, |day| day.and_time(date.time()))
+}
+
+/// Add (or subtract) whole calendar months, clamping the day to the end of the target month.
+fn add_months(date: NaiveDateTime, months: i32) -> NaiveDateTime {
+	let shifted = if months >= 0 {
+		date.checked_add_months(Months::new(months as u32))
+	} else {
+		date.checked_sub_months(Months::new(months.unsigned_abs()))
+	};
+	shifted.unwrap_or(date)
+}
+
+fn get_days_from_month(year: i32, month: u32) -> i64 {
+	last_day_of_month(year, month).map_or(31, |day| day.day().into())
+}
+
+/// The last day of the month, also for the last month chrono can represent.
+fn last_day_of_month(year: i32, month: u32) -> Option<NaiveDate> {
+	(28..=31).rev().find_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+}
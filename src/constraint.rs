@@ -0,0 +1,390 @@
+//! Constraint based layout, placing widgets by linear relations between their edges.
+//!
+//! A [`ConstraintLayout`] runs between the two phases of a [`WidgetPlacer`]: the widgets are
+//! created as usual, then added to the layout, which solves the constraints and moves them before
+//! they are interacted with.
+//!
+//! ```no_run
+//! # use grus_gui_lib::{Create, Label, Button, Paint, WidgetPlacer};
+//! # use grus_gui_lib::constraint::ConstraintLayout;
+//! # fn draw(ui: &mut egui::Ui) {
+//! let mut placer = WidgetPlacer::new(ui);
+//! let mut label = placer.create(Label::new("Name"));
+//! let mut button = placer.create(Button::new("Edit"));
+//!
+//! let mut layout = ConstraintLayout::new();
+//! let label_rect = layout.add(&label);
+//! let button_rect = layout.add(&button);
+//! // The label ends 8 points before the button, on the same line.
+//! layout.constrain(label_rect.right().equals(button_rect.left() - 8.0)).unwrap();
+//! layout.constrain(label_rect.center_y().equals(button_rect.center_y())).unwrap();
+//! // Required, so it wins over the button keeping its size.
+//! layout.constrain(button_rect.width().at_least(80.0)).unwrap();
+//! layout.apply(label_rect, &mut label);
+//! layout.apply(button_rect, &mut button);
+//!
+//! let response = label.interact(ui);
+//! ui.paint(&label, &response);
+//! let response = button.interact(ui);
+//! ui.paint(&button, &response);
+//! # }
+//! ```
+//!
+//! [`WidgetPlacer`]: crate::WidgetPlacer
+
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+use egui::{Rect, Vec2};
+
+use crate::solver::{Relation, Solver};
+use crate::Placed;
+
+/// A value solved by a [`ConstraintLayout`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Variable(usize);
+
+/// A linear expression of [`Variable`]s.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Expression {
+	terms: Vec<(Variable, f32)>,
+	constant: f32,
+}
+
+impl Expression {
+	pub fn constant(constant: f32) -> Self {
+		Expression { terms: Vec::new(), constant }
+	}
+
+	/// The constraint `self == other`.
+	pub fn equals(self, other: impl Into<Expression>) -> Constraint {
+		Constraint::new(self - other.into(), Relation::Equal)
+	}
+
+	/// The constraint `self <= other`.
+	pub fn at_most(self, other: impl Into<Expression>) -> Constraint {
+		Constraint::new(self - other.into(), Relation::LessOrEqual)
+	}
+
+	/// The constraint `self >= other`.
+	pub fn at_least(self, other: impl Into<Expression>) -> Constraint {
+		Constraint::new(self - other.into(), Relation::GreaterOrEqual)
+	}
+}
+
+impl Variable {
+	/// The constraint `self == other`.
+	pub fn equals(self, other: impl Into<Expression>) -> Constraint {
+		Expression::from(self).equals(other)
+	}
+
+	/// The constraint `self <= other`.
+	pub fn at_most(self, other: impl Into<Expression>) -> Constraint {
+		Expression::from(self).at_most(other)
+	}
+
+	/// The constraint `self >= other`.
+	pub fn at_least(self, other: impl Into<Expression>) -> Constraint {
+		Expression::from(self).at_least(other)
+	}
+}
+
+impl From<Variable> for Expression {
+	fn from(variable: Variable) -> Self {
+		Expression { terms: vec![(variable, 1.0)], constant: 0.0 }
+	}
+}
+
+impl From<f32> for Expression {
+	fn from(constant: f32) -> Self {
+		Expression::constant(constant)
+	}
+}
+
+impl<T: Into<Expression>> Add<T> for Expression {
+	type Output = Expression;
+	fn add(mut self, other: T) -> Expression {
+		let other = other.into();
+		self.terms.extend(other.terms);
+		self.constant += other.constant;
+		self
+	}
+}
+
+impl<T: Into<Expression>> Sub<T> for Expression {
+	type Output = Expression;
+	fn sub(self, other: T) -> Expression {
+		self + -other.into()
+	}
+}
+
+impl Mul<f32> for Expression {
+	type Output = Expression;
+	fn mul(mut self, factor: f32) -> Expression {
+		for (_, coefficient) in &mut self.terms {
+			*coefficient *= factor;
+		}
+		self.constant *= factor;
+		self
+	}
+}
+
+impl Mul<Expression> for f32 {
+	type Output = Expression;
+	fn mul(self, expression: Expression) -> Expression {
+		expression * self
+	}
+}
+
+impl Neg for Expression {
+	type Output = Expression;
+	fn neg(self) -> Expression {
+		self * -1.0
+	}
+}
+
+impl<T: Into<Expression>> Add<T> for Variable {
+	type Output = Expression;
+	fn add(self, other: T) -> Expression {
+		Expression::from(self) + other
+	}
+}
+
+impl<T: Into<Expression>> Sub<T> for Variable {
+	type Output = Expression;
+	fn sub(self, other: T) -> Expression {
+		Expression::from(self) - other
+	}
+}
+
+impl Mul<f32> for Variable {
+	type Output = Expression;
+	fn mul(self, factor: f32) -> Expression {
+		Expression::from(self) * factor
+	}
+}
+
+impl Mul<Variable> for f32 {
+	type Output = Expression;
+	fn mul(self, variable: Variable) -> Expression {
+		Expression::from(variable) * self
+	}
+}
+
+impl Neg for Variable {
+	type Output = Expression;
+	fn neg(self) -> Expression {
+		-Expression::from(self)
+	}
+}
+
+/// How hard the solver tries to satisfy a [`Constraint`].
+///
+/// Required constraints must hold, the others are satisfied as far as possible,
+/// a stronger one always winning over any number of weaker ones.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Strength(f64);
+
+impl Strength {
+	pub const REQUIRED: Strength = Strength(Solver::REQUIRED);
+	pub const STRONG: Strength = Strength(1_000_000.0);
+	pub const MEDIUM: Strength = Strength(1_000.0);
+	pub const WEAK: Strength = Strength(1.0);
+
+	/// A strength in between the predefined ones, e.g. `Strength::new(0.0, 1.0, 500.0)`
+	/// for something between medium and strong.
+	pub fn new(strong: f64, medium: f64, weak: f64) -> Self {
+		let clamp = |value: f64| value.clamp(0.0, 1000.0);
+		Strength(clamp(strong) * 1_000_000.0 + clamp(medium) * 1_000.0 + clamp(weak))
+	}
+}
+
+/// A linear equality or inequality between [`Expression`]s.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Constraint {
+	/// `expression OP 0`
+	expression: Expression,
+	relation: Relation,
+	strength: Strength,
+}
+
+impl Constraint {
+	fn new(expression: Expression, relation: Relation) -> Self {
+		Constraint { expression, relation, strength: Strength::REQUIRED }
+	}
+
+	/// Set how hard to try to satisfy this constraint. It is required by default.
+	#[must_use]
+	pub fn strength(mut self, strength: Strength) -> Self {
+		self.strength = strength;
+		self
+	}
+}
+
+/// A required [`Constraint`] conflicts with the ones added before.
+#[derive(Clone, Debug)]
+pub struct UnsatisfiableConstraint(pub Constraint);
+
+impl fmt::Display for UnsatisfiableConstraint {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "unsatisfiable constraint: {:?}", self.0)
+	}
+}
+
+impl std::error::Error for UnsatisfiableConstraint {}
+
+/// The edges of a widget added to a [`ConstraintLayout`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WidgetRect {
+	left: Variable,
+	top: Variable,
+	width: Variable,
+	height: Variable,
+}
+
+impl WidgetRect {
+	pub fn left(&self) -> Expression {
+		self.left.into()
+	}
+
+	pub fn top(&self) -> Expression {
+		self.top.into()
+	}
+
+	pub fn right(&self) -> Expression {
+		self.left + self.width
+	}
+
+	pub fn bottom(&self) -> Expression {
+		self.top + self.height
+	}
+
+	pub fn width(&self) -> Expression {
+		self.width.into()
+	}
+
+	pub fn height(&self) -> Expression {
+		self.height.into()
+	}
+
+	pub fn center_x(&self) -> Expression {
+		self.left + self.width * 0.5
+	}
+
+	pub fn center_y(&self) -> Expression {
+		self.top + self.height * 0.5
+	}
+}
+
+/// Places widgets by solving linear constraints between their edges, with the Cassowary
+/// algorithm.
+///
+/// Every added widget stays where its [`WidgetPlacer`](crate::WidgetPlacer) put it unless a
+/// constraint moves it, and keeps its size unless a stronger than [`Strength::STRONG`] constraint
+/// changes it. Widgets from different placers can be related, as their rects are absolute.
+///
+/// ```
+/// # use egui::{pos2, vec2, Rect};
+/// # use grus_gui_lib::constraint::ConstraintLayout;
+/// let mut layout = ConstraintLayout::new();
+/// let a = layout.add_rect(Rect::from_min_size(pos2(0.0, 0.0), vec2(50.0, 20.0)));
+/// let b = layout.add_rect(Rect::from_min_size(pos2(0.0, 30.0), vec2(80.0, 20.0)));
+/// let column_width = layout.variable();
+/// layout.constrain(a.width().equals(column_width)).unwrap();
+/// layout.constrain(b.width().equals(column_width)).unwrap();
+/// layout.constrain(b.left().equals(a.right() + 8.0)).unwrap();
+/// let (a_rect, b_rect) = (layout.rect(a), layout.rect(b));
+/// assert!((b_rect.left() - a_rect.right() - 8.0).abs() < 1e-3);
+/// assert!((a_rect.width() - b_rect.width()).abs() < 1e-3);
+///
+/// layout.constrain(a.left().equals(10.0)).unwrap();
+/// assert!(layout.constrain(a.left().equals(20.0)).is_err());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ConstraintLayout {
+	solver: Solver,
+	variables: usize,
+}
+
+impl ConstraintLayout {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// A new free variable, e.g. for a width shared by several widgets.
+	pub fn variable(&mut self) -> Variable {
+		self.variables += 1;
+		Variable(self.variables - 1)
+	}
+
+	/// Adds a laid out widget, returning its edges to constrain.
+	pub fn add(&mut self, widget: &impl Placed) -> WidgetRect {
+		self.add_rect(widget.placed_rect())
+	}
+
+	/// Adds a rect that initially is at `rect`.
+	pub fn add_rect(&mut self, rect: Rect) -> WidgetRect {
+		let widget = WidgetRect {
+			left: self.variable(),
+			top: self.variable(),
+			width: self.variable(),
+			height: self.variable(),
+		};
+		let stays = [
+			widget.width.at_least(0.0),
+			widget.height.at_least(0.0),
+			widget.width.equals(rect.width()).strength(Strength::STRONG),
+			widget.height.equals(rect.height()).strength(Strength::STRONG),
+			widget.left.equals(rect.left()).strength(Strength::WEAK),
+			widget.top.equals(rect.top()).strength(Strength::WEAK),
+		];
+		for constraint in stays {
+			self.constrain(constraint).expect("stays are always satisfiable");
+		}
+		widget
+	}
+
+	/// Adds a constraint, which is solved right away.
+	///
+	/// A required constraint that conflicts with the ones added before is not added.
+	pub fn constrain(&mut self, constraint: Constraint) -> Result<(), UnsatisfiableConstraint> {
+		let Expression { terms, constant } = &constraint.expression;
+		let terms: Vec<_> = terms
+			.iter()
+			.map(|&(Variable(variable), coefficient)| (variable, coefficient as f64))
+			.collect();
+		self.solver
+			.add_constraint(&terms, *constant as f64, constraint.relation, constraint.strength.0)
+			.map_err(|_| UnsatisfiableConstraint(constraint))
+	}
+
+	/// Lets `variable` be moved with [`Self::suggest`], e.g. while dragging, as hard as
+	/// `strength`. A required strength is treated as just below required.
+	pub fn edit(&mut self, variable: Variable, strength: Strength) {
+		self.solver.add_edit_variable(variable.0, strength.0);
+	}
+
+	/// Suggests a value for a variable added with [`Self::edit`], which is solved right away.
+	/// Does nothing for other variables.
+	pub fn suggest(&mut self, variable: Variable, value: f32) {
+		self.solver.suggest_value(variable.0, value as f64);
+	}
+
+	/// The solved value of a variable.
+	pub fn value(&self, variable: Variable) -> f32 {
+		self.solver.value(variable.0) as f32
+	}
+
+	/// The solved rect of a widget.
+	pub fn rect(&self, widget: WidgetRect) -> Rect {
+		Rect::from_min_size(
+			egui::pos2(self.value(widget.left), self.value(widget.top)),
+			Vec2::new(self.value(widget.width), self.value(widget.height)),
+		)
+	}
+
+	/// Moves a laid out widget to its solved rect.
+	pub fn apply(&self, widget: WidgetRect, laid_out: &mut impl Placed) {
+		laid_out.place(self.rect(widget));
+	}
+}
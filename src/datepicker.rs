@@ -23,43 +23,54 @@
 //!
 //! [ex]: ./examples/simple.rs
 
-use core::ops::{Bound, RangeBounds};
+use core::ops::RangeBounds;
+use std::collections::BTreeSet;
 use std::hash::Hash;
 
 pub use chrono::naive::NaiveDateTime;
-use chrono::naive::{MAX_DATE, MIN_DATE};
-use chrono::{prelude::*, Duration};
+use chrono::prelude::*;
 use eframe::{
 	egui,
-	egui::{Area, Color32, DragValue, Frame, Id, Key, Order, Response, RichText, Ui, Widget},
+	egui::{Area, Color32, Frame, Id, Key, Order, Rect, Response, Ui, Widget},
 };
-use num_traits::FromPrimitive;
 
+use crate::calendar::{Calendar, Selection};
+use crate::{Button, Create, LaidOutButton, Paint, Placed, WidgetPlacer};
+pub use crate::calendar::{DayDecoration, Locale};
+
+/// A button showing the date, which opens a [`Calendar`] in a popup when clicked.
+///
 /// Default values of fields are:
-/// - sunday_first: `false`
+/// - locale: [`Locale::EN`]
+/// - first_weekday: taken from the locale
 /// - movable: `false`
-/// - format_string: `"%Y-%m-%d"`
+/// - format_string: taken from the locale
 /// - weekend_func: `date.weekday() == Weekday::Sat || date.weekday() == Weekday::Sun`
 /// - placment: just underneath the date picker's button
+/// - confirm_buttons: `false`, every click in the calendar changes the date right away
+/// - close_on_select: `false`
+///
+/// While the popup is open it can be driven from the keyboard, see [`Calendar`].
+/// In addition `Enter` confirms the selection and closes the popup, `Escape` closes it.
+///
+/// Besides being added to a [`Ui`] directly, it can be laid out with a [`WidgetPlacer`] like the
+/// other widgets of this crate, see [`LaidOutDatePicker`].
 pub struct DatePicker<'a, 'b, R>
 where
 	R: RangeBounds<NaiveDateTime>,
 {
 	id: Id,
-	date: &'a mut NaiveDateTime,
-	sunday_first: bool,
+	calendar: Calendar<'a, 'b, R>,
 	movable: bool,
-	format_string: String,
-	weekend_color: Color32,
-	weekend_func: fn(&NaiveDateTime) -> bool,
-	highlight_weekend: bool,
-
-	// when set, the date picker will restrict dates to the given range.
-	allowed_range: Option<&'b R>,
+	// when set, overrides the locale's date format.
+	format_string: Option<String>,
 	// when set, will allow place the popup in the given position
 	placement: Option<egui::Align2>,
 
 	position_offset: egui::Vec2,
+
+	confirm_buttons: bool,
+	close_on_select: bool,
 }
 
 impl<'a, 'b, R> DatePicker<'a, 'b, R>
@@ -68,26 +79,94 @@ where
 {
 	/// Create new date picker with unique id and mutable reference to date.
 	pub fn new<T: Hash>(id: T, date: &'a mut NaiveDateTime) -> Self {
+		let id = Id::new(id);
+		Self::with_calendar(id, Calendar::new(id, date))
+	}
+
+	/// Create new date picker with unique id and mutable reference to a date, which may be unset.
+	/// While the date is `None` the button shows the locale's placeholder text, otherwise a
+	/// button next to it clears the date.
+	pub fn new_optional<T: Hash>(id: T, date: &'a mut Option<NaiveDateTime>) -> Self {
+		let id = Id::new(id);
+		Self::with_calendar(id, Calendar::new_optional(id, date))
+	}
+
+	fn with_calendar(id: Id, mut calendar: Calendar<'a, 'b, R>) -> Self {
+		calendar.in_popup = true;
 		Self {
-			id: Id::new(id),
-			date,
-			sunday_first: false,
+			id,
+			calendar,
 			movable: false,
-			format_string: String::from("%Y-%m-%d"),
-			weekend_color: Color32::from_rgb(196, 0, 0),
-			weekend_func: |date| date.weekday() == Weekday::Sat || date.weekday() == Weekday::Sun,
-			highlight_weekend: true,
-			allowed_range: None,
+			format_string: None,
 			placement: None,
 			position_offset: egui::Vec2 { x: 0., y: 0. },
+			confirm_buttons: false,
+			close_on_select: false,
 		}
 	}
 
+	/// Open the popup on the month of this date while no date is selected.
+	/// Default is the current month
+	#[must_use]
+	pub fn default_date(mut self, date: NaiveDate) -> Self {
+		self.calendar = self.calendar.default_date(date);
+		self
+	}
+
+	/// Select a range of dates instead of a single one, see [`Calendar::range_end`].
+	#[must_use]
+	pub fn range_end(mut self, end: &'a mut NaiveDateTime) -> Self {
+		self.calendar = self.calendar.range_end(end);
+		self
+	}
+
+	/// Select any number of individual days instead of a single date, see
+	/// [`Calendar::multi_select`]. The button shows the first selected day and how many more
+	/// there are.
+	///
+	/// Since every click changes the selection, [`Self::close_on_select`] has no effect in this
+	/// mode.
+	#[must_use]
+	pub fn multi_select(mut self, dates: &'a mut BTreeSet<NaiveDate>) -> Self {
+		self.calendar = self.calendar.multi_select(dates);
+		self
+	}
+
+	/// In multi-date mode, don't allow selecting more than this many days.
+	#[must_use]
+	pub fn max_selected(mut self, max: usize) -> Self {
+		self.calendar = self.calendar.max_selected(max);
+		self
+	}
+
+	/// Set the language of month and weekday names, the first day of the week and the default
+	/// date format.
+	#[must_use]
+	pub fn locale(mut self, locale: Locale) -> Self {
+		self.calendar = self.calendar.locale(locale);
+		self
+	}
+
 	/// If flag is set to true then first day in calendar will be sunday otherwise monday.
-	/// Default is false
+	/// Default is taken from the locale
 	#[must_use]
 	pub fn sunday_first(mut self, flag: bool) -> Self {
-		self.sunday_first = flag;
+		self.calendar = self.calendar.sunday_first(flag);
+		self
+	}
+
+	/// Set the first day in calendar, overriding the one of the locale.
+	#[must_use]
+	pub fn first_weekday(mut self, weekday: Weekday) -> Self {
+		self.calendar = self.calendar.first_weekday(weekday);
+		self
+	}
+
+	/// Show this many consecutive months side by side in the popup.
+	/// Default is 1
+	#[must_use]
+	pub fn months(mut self, months: u32) -> Self {
+		self.calendar = self.calendar.months(months);
 		self
 	}
 
@@ -104,7 +183,7 @@ where
 	///See the [chrono::format::strftime](https://docs.rs/chrono/0.4.19/chrono/format/strftime/index.html) for the specification.
 	#[must_use]
 	pub fn date_format(mut self, new_format: &impl ToString) -> Self {
-		self.format_string = new_format.to_string();
+		self.format_string = Some(new_format.to_string());
 		self
 	}
 
@@ -112,26 +191,62 @@ where
 	///color.
 	#[must_use]
 	pub fn highlight_weekend(mut self, highlight: bool) -> Self {
-		self.highlight_weekend = highlight;
+		self.calendar = self.calendar.highlight_weekend(highlight);
 		self
 	}
 
 	///Set weekends highlighting color.
 	#[must_use]
 	pub fn highlight_weekend_color(mut self, color: Color32) -> Self {
-		self.weekend_color = color;
+		self.calendar = self.calendar.highlight_weekend_color(color);
+		self
+	}
+
+	/// Show ISO week numbers in the calendar, see [`Calendar::show_week_numbers`].
+	#[must_use]
+	pub fn show_week_numbers(mut self, flag: bool) -> Self {
+		self.calendar = self.calendar.show_week_numbers(flag);
 		self
 	}
 
 	/// Set function, which will decide if date is a weekend day or not.
 	pub fn weekend_days(mut self, is_weekend: fn(&NaiveDateTime) -> bool) -> Self {
-		self.weekend_func = is_weekend;
+		self.calendar = self.calendar.weekend_days(is_weekend);
 		self
 	}
 
 	/// The date picker will restrict dates to the given range.
 	pub fn restrict_range(mut self, allowed_range: &'b R) -> Self {
-		self.allowed_range = Some(allowed_range);
+		self.calendar = self.calendar.restrict_range(allowed_range);
+		self
+	}
+
+	/// The date picker will only allow selecting times of day between `start` and `end`, on
+	/// every day, see [`Calendar::daily_time_window`].
+	pub fn daily_time_window(mut self, start: NaiveTime, end: NaiveTime) -> Self {
+		self.calendar = self.calendar.daily_time_window(start, end);
+		self
+	}
+
+	/// If flag is set to true then seconds can be edited along with hours and minutes.
+	/// Default is false
+	#[must_use]
+	pub fn show_seconds(mut self, flag: bool) -> Self {
+		self.calendar = self.calendar.show_seconds(flag);
+		self
+	}
+
+	/// The date picker will only allow selecting days in the given set.
+	/// This can be combined with [`Self::restrict_range`].
+	pub fn restrict_dates(mut self, allowed_dates: &'b BTreeSet<NaiveDate>) -> Self {
+		self.calendar = self.calendar.restrict_dates(allowed_dates);
+		self
+	}
+
+	/// Set a closure, which decides how each day in the calendar is presented and whether it can
+	/// be selected at all, see [`Calendar::decorate_days`].
+	pub fn decorate_days(mut self, decorator: impl Fn(NaiveDate) -> DayDecoration + 'b) -> Self {
+		self.calendar = self.calendar.decorate_days(decorator);
 		self
 	}
 
@@ -149,235 +264,102 @@ where
 		self
 	}
 
-	/// Draw names of week days as 7 columns of grid without calling `Ui::end_row`
-	fn show_grid_header(&mut self, ui: &mut Ui) {
-		let day_indexes = if self.sunday_first {
-			[6, 0, 1, 2, 3, 4, 5]
-		} else {
-			[0, 1, 2, 3, 4, 5, 6]
-		};
-		for i in day_indexes {
-			let b = Weekday::from_u8(i).unwrap();
-			ui.label(b.to_string());
-		}
-	}
-
-	/// Get number of days between first day of the month and Monday ( or Sunday if field
-	/// `sunday_first` is set to `true` )
-	fn get_start_offset_of_calendar(&self, first_day: &NaiveDateTime) -> u32 {
-		if self.sunday_first {
-			first_day.weekday().num_days_from_sunday()
-		} else {
-			first_day.weekday().num_days_from_monday()
-		}
-	}
-
-	/// Get number of days between first day of the next month and Monday ( or Sunday if field
-	/// `sunday_first` is set to `true` )
-	fn get_end_offset_of_calendar(&self, first_day: &NaiveDateTime) -> u32 {
-		if self.sunday_first {
-			(7 - (first_day).weekday().num_days_from_sunday()) % 7
-		} else {
-			(7 - (first_day).weekday().num_days_from_monday()) % 7
-		}
+	/// If flag is set to true then the popup gets a footer with "Today", "Cancel" and "OK"
+	/// buttons, plus "Clear" for an optional date, and the date is only changed once the
+	/// selection is confirmed with "OK" or `Enter`. Closing the popup in any other way discards
+	/// the selection.
+	/// Default is false
+	#[must_use]
+	pub fn confirm_buttons(mut self, flag: bool) -> Self {
+		self.confirm_buttons = flag;
+		self
 	}
 
-	fn show_calendar_grid(&mut self, ui: &mut Ui) {
-		egui::Grid::new("calendar").min_col_width(30.0).show(ui, |ui| {
-			self.show_grid_header(ui);
-			let first_day_of_current_month = self.date.with_day(1).unwrap();
-			let start_offset = self.get_start_offset_of_calendar(&first_day_of_current_month);
-			let days_in_month = get_days_from_month(self.date.year(), self.date.month());
-			let first_day_of_next_month =
-				first_day_of_current_month + Duration::days(days_in_month);
-			let end_offset = self.get_end_offset_of_calendar(&first_day_of_next_month);
-			let start_date = first_day_of_current_month - Duration::days(start_offset.into());
-			for i in 0..(start_offset as i64 + days_in_month + end_offset as i64) {
-				if i % 7 == 0 {
-					ui.end_row();
-				}
-				let d = start_date + Duration::days(i);
-				self.show_day_button(d, ui);
-			}
-		});
+	/// If flag is set to true then the popup closes as soon as a day is selected, or in range
+	/// mode once the range is finished. This confirms the selection.
+	/// Default is false
+	#[must_use]
+	pub fn close_on_select(mut self, flag: bool) -> Self {
+		self.close_on_select = flag;
+		self
 	}
 
-	fn show_day_button(&mut self, date: NaiveDateTime, ui: &mut Ui) {
-		let mut is_enabled = self.date != &date;
-
-		if let Some(range) = self.allowed_range {
-			// round the date up and down to the nearest date
-			let day_beginning = date.date().and_hms(0, 0, 0);
-			let day_ending = day_beginning + Duration::days(1);
-
-			is_enabled &= range.contains(&day_beginning) | range.contains(&day_ending);
-		};
-
-		ui.centered_and_justified(|ui| {
-			let mut button = egui::Button::new(date.day().to_string());
-
-			if self.date.month() != date.month() {
-				button = button.frame(false);
-			} else if self.date == &date {
-				// if the date is the selected date,
-				// give the button an fill with the 'selection style'
-				button = button.fill(ui.style().visuals.selection.bg_fill);
-			}
-			if self.highlight_weekend && (self.weekend_func)(&date) {
-				ui.style_mut().visuals.override_text_color = Some(self.weekend_color);
+	/// Draw the "Today", "Clear", "Cancel" and "OK" buttons.
+	/// Returns `Some(true)` to confirm the selection and `Some(false)` to discard it.
+	fn show_footer(&mut self, ui: &mut Ui, clearable: bool) -> Option<bool> {
+		let locale = self.calendar.locale;
+		let mut action = None;
+		ui.separator();
+		ui.horizontal(|ui| {
+			if ui.button(locale.today).clicked() {
+				self.calendar.select_today(ui);
 			}
-			if ui.add_enabled(is_enabled, button).clicked() {
-				*self.date = date;
+			if clearable && ui.button(locale.clear).clicked() {
+				self.calendar.clear(ui);
 			}
+			ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+				if ui.button(locale.ok).clicked() {
+					action = Some(true);
+				}
+				if ui.button(locale.cancel).clicked() {
+					action = Some(false);
+				}
+			});
 		});
+		action
 	}
 
-	fn show_time_editor(&mut self, ui: &mut Ui) {
-		let (hour_range, min_range) = match self.allowed_range {
-			Some(range) => {
-				let day_beginning = self.date.date().and_hms(0, 0, 0);
-				let day_ending = day_beginning + Duration::days(1);
-
-				let (start_hour, start_min) = match range.start_bound() {
-					Bound::Included(dt) if day_beginning < *dt => {
-						if dt.hour() == self.date.hour() {
-							(dt.hour(), dt.minute())
-						} else {
-							(dt.hour(), 0)
-						}
-					}
-					Bound::Excluded(dt) if day_beginning <= *dt => {
-						if dt.hour() == self.date.hour() {
-							(dt.hour(), dt.minute() + 1)
-						} else {
-							(dt.hour(), 0)
-						}
-					}
-					_ => (0, 0),
-				};
-				let (end_hour, end_min) = match range.end_bound() {
-					Bound::Included(dt) if day_ending > *dt => {
-						if dt.hour() == self.date.hour() {
-							(dt.hour(), dt.minute())
-						} else {
-							(dt.hour(), 59)
-						}
-					}
-					Bound::Excluded(dt) if day_ending >= *dt => {
-						if dt.hour() == self.date.hour() {
-							(dt.hour(), dt.minute() - 1)
-						} else {
-							(dt.hour(), 59)
-						}
-					}
-					_ => (23, 59),
-				};
-
-				(start_hour..=end_hour, start_min..=end_min)
-			}
-			None => (0..=23, 0..=59),
-		};
-
-		let curr_hour = self.date.hour() as i64;
-		let curr_min = self.date.minute() as i64;
-		let mut hour = curr_hour;
-		let mut min = curr_min;
-
-		ui.add(egui::DragValue::new(&mut hour).clamp_range(hour_range));
-		ui.label(":");
-		ui.add(egui::DragValue::new(&mut min).clamp_range(min_range));
-
-		*self.date += Duration::hours(hour - curr_hour) + Duration::minutes(min - curr_min);
+	fn format_string(&self) -> &str {
+		self.format_string.as_deref().unwrap_or(self.calendar.locale.date_format)
 	}
 
-	/// Draw current month and buttons for next and previous month.
-	fn show_header(&mut self, ui: &mut Ui) {
-		ui.horizontal(|ui| {
-			self.show_time_editor(ui);
-			self.show_month_control(ui);
-			self.show_year_control(ui);
-		});
+	/// The text of the button opening the popup.
+	fn button_text(&self) -> String {
+		let selection = self.calendar.selection();
+		let format = |date: NaiveDateTime| date.format(self.format_string()).to_string();
+		let first_date = match &selection.dates {
+			Some(dates) => dates.first().map(|date| date.and_time(NaiveTime::MIN)),
+			None => selection.date,
+		};
+		match (first_date, selection.dates.map_or(1, |dates| dates.len())) {
+			(None, _) => self.calendar.locale.placeholder.to_string(),
+			(Some(date), 1) => format(date),
+			(Some(date), count) => format!("{} +{}", format(date), count - 1),
+		}
 	}
 
-	/// Draw button with text and add duration to current date when that button is clicked.
-	fn date_step_button(&mut self, ui: &mut Ui, text: impl ToString, duration: Duration) {
-		let new_date = *self.date + duration;
-
-		ui.button(text.to_string())
-			.clicked()
-			.then(|| match self.allowed_range {
-				None => *self.date = new_date,
-				// Ensure the bound is never left.
-				Some(range) => match (range.start_bound(), range.end_bound()) {
-					(Bound::Included(lower_b), _) if new_date < *lower_b => *self.date = *lower_b,
-					(Bound::Excluded(lower_b), _) if new_date <= *lower_b
-						=> *self.date = *lower_b + Duration::nanoseconds(1),
-
-					(_, Bound::Included(upper_b)) if *upper_b < new_date => *self.date = *upper_b,
-					(_, Bound::Excluded(upper_b)) if *upper_b <= new_date
-						=> *self.date = *upper_b - Duration::nanoseconds(1),
-
-					_ => *self.date = new_date,
-				}
-			});
+	/// Is there a date, which can be cleared with the button next to the date picker's button?
+	fn has_clear_button(&self) -> bool {
+		self.calendar.can_clear()
 	}
 
-	/// Draw drag value widget with current year and two buttons which substract and add 365 days
-	/// to current date.
-	fn show_year_control(&mut self, ui: &mut Ui) {
-		self.date_step_button(ui, "⬅", Duration::days(-365));
-
-		// label used instead of drag value, if there is a range restriction imposed
-		// TODO: change clamp_range to enforce imposed range restriction
-		if self.allowed_range.is_none() {
-			let mut drag_year = self.date.year();
-			ui.add(DragValue::new(&mut drag_year).clamp_range(MIN_DATE.year()..=MAX_DATE.year()));
+	/// Handle clicks on the date picker's button and the clear button, and show the popup
+	/// while it is open.
+	fn show_popup(&mut self, ui: &mut Ui, button_response: &mut Response, clear_clicked: bool) {
+		let committed = self.calendar.selection();
+		let draft_id = self.id.with("draft");
+		let clearable = self.calendar.is_clearable();
 
-			if drag_year != self.date.year() {
-				// `unwrap()` should be safe due to drag value being clamped to the accepted range.
-				*self.date = self.date.with_year(drag_year).unwrap();
-			}
-		} else {
-			ui.label(self.date.year().to_string());
+		if clear_clicked {
+			self.calendar.clear(ui);
+			ui.memory_mut(|m| m.close_popup());
 		}
-
-		self.date_step_button(ui, "➡", Duration::days(365));
-	}
-
-	/// Draw label(will be combobox in future) with current month and two buttons which substract and add 30 days
-	/// to current date.
-	fn show_month_control(&mut self, ui: &mut Ui) {
-		self.date_step_button(ui, "⬅", Duration::days(-30));
-		let month_string = &chrono::Month::from_u32(self.date.month()).unwrap().name()[..3];
-		// TODO: When https://github.com/emilk/egui/pull/543 is merged try to change label to combo box.
-		ui.add(egui::Label::new(
-			RichText::new(month_string).text_style(egui::TextStyle::Monospace),
-		));
-		// let mut selected = self.date.month0() as usize;
-		// egui::ComboBox::from_id_source(self.id.with("month_combo_box"))
-		//	 .selected_text(selected)
-		//	 .show_index(ui, &mut selected, 12, |i| {
-		//		 chrono::Month::from_usize(i + 1).unwrap().name().to_string()
-		//	 });
-		// if selected != self.date.month0() as usize {
-		//	 *self.date = self.date.with_month0(selected as u32).unwrap();
-		// }
-		self.date_step_button(ui, "➡", Duration::days(30));
-	}
-}
-
-impl<'a, 'b, R> Widget for DatePicker<'a, 'b, R>
-where
-	R: RangeBounds<NaiveDateTime>,
-{
-	fn ui(mut self, ui: &mut Ui) -> Response {
-		let formated_date = self.date.format(&self.format_string);
-		let button_response = ui.button(formated_date.to_string());
 		if button_response.clicked() {
 			ui.memory_mut(|m| m.toggle_popup(self.id));
+			// focus starts on the selected day when the popup opens
+			self.calendar.reset(ui);
+			ui.data_mut(|d| d.insert_temp(draft_id, committed.clone()));
 		}
 
 		if ui.memory(|m| m.is_popup_open(self.id)) {
+			// with confirm buttons, the calendar edits a draft until the selection is confirmed
+			if self.confirm_buttons {
+				let draft = ui.data(|d| d.get_temp::<Selection>(draft_id)).unwrap_or_else(|| committed.clone());
+				self.calendar.set_selection(draft);
+			}
+
+			let mut output = Default::default();
+			let mut footer_action = None;
 			let mut area = Area::new(self.id).order(Order::Foreground).constrain(true);
 
 			if let Some(align) = self.placement {
@@ -391,35 +373,141 @@ where
 			let area_response = area
 				.show(ui.ctx(), |ui| {
 					Frame::popup(ui.style()).show(ui, |ui| {
-						self.show_header(ui);
-						self.show_calendar_grid(ui);
+						output = self.calendar.show_contents(ui);
+						if self.confirm_buttons {
+							footer_action = self.show_footer(ui, clearable);
+						}
 					});
 				})
 				.response;
 
-			if !button_response.clicked()
-				&& (ui.input(|i| i.key_pressed(Key::Escape) || area_response.clicked_elsewhere()))
-			{
+			let confirmed = output.confirmed
+				|| footer_action == Some(true)
+				|| self.close_on_select && output.selected;
+			let cancelled = footer_action == Some(false)
+				|| ui.input(|i| i.key_pressed(Key::Escape))
+				|| area_response.clicked_elsewhere();
+
+			if self.confirm_buttons {
+				let draft = self.calendar.selection();
+				ui.data_mut(|d| d.insert_temp(draft_id, draft));
+				if !confirmed {
+					self.calendar.set_selection(committed.clone());
+				}
+			}
+
+			if !button_response.clicked() && (confirmed || cancelled) {
 				ui.memory_mut(|m| m.toggle_popup(self.id));
 			}
 		}
+
+		if self.calendar.selection() != committed {
+			button_response.mark_changed();
+		}
+	}
+}
+
+impl<'a, 'b, R> Widget for DatePicker<'a, 'b, R>
+where
+	R: RangeBounds<NaiveDateTime>,
+{
+	fn ui(mut self, ui: &mut Ui) -> Response {
+		let text = self.button_text();
+		let has_clear_button = self.has_clear_button();
+		let (mut button_response, clear_clicked) = ui
+			.horizontal(|ui| {
+				let response = ui.button(text);
+				let clear = has_clear_button && ui.small_button("✖").clicked();
+				(response, clear)
+			})
+			.inner;
+		self.show_popup(ui, &mut button_response, clear_clicked);
 		button_response
 	}
 }
 
-// https://stackoverflow.com/a/58188385
-fn get_days_from_month(year: i32, month: u32) -> i64 {
-	NaiveDate::from_ymd(
-		match month {
-			12 => year + 1,
-			_ => year,
-		},
-		match month {
-			12 => 1,
-			_ => month + 1,
-		},
-		1,
-	)
-	.signed_duration_since(NaiveDate::from_ymd(year, month, 1))
-	.num_days()
+impl<'a, 'b, R> Create<DatePicker<'a, 'b, R>> for WidgetPlacer<'_>
+where
+	R: RangeBounds<NaiveDateTime>,
+{
+	type LaidOutWidget = LaidOutDatePicker<'a, 'b, R>;
+	#[track_caller]
+	fn create(&mut self, datepicker: DatePicker<'a, 'b, R>) -> LaidOutDatePicker<'a, 'b, R> {
+		let button = self.create(Button::new(datepicker.button_text()));
+		let clear_button = if datepicker.has_clear_button() {
+			Some(self.create(Button::new("✖").small()))
+		} else {
+			None
+		};
+		LaidOutDatePicker { datepicker, button, clear_button, clear_response: None }
+	}
+}
+
+/// A [`DatePicker`] laid out with a [`WidgetPlacer`].
+///
+/// Unlike the other laid out widgets, [`Self::interact`] needs mutable access, because it also
+/// shows the popup while that is open.
+pub struct LaidOutDatePicker<'a, 'b, R>
+where
+	R: RangeBounds<NaiveDateTime>,
+{
+	datepicker: DatePicker<'a, 'b, R>,
+	button: LaidOutButton,
+	clear_button: Option<LaidOutButton>,
+	clear_response: Option<Response>,
+}
+
+impl<'a, 'b, R> LaidOutDatePicker<'a, 'b, R>
+where
+	R: RangeBounds<NaiveDateTime>,
+{
+	pub fn reposition(&mut self, y: f32) {
+		self.button.reposition(y);
+		if let Some(clear_button) = &mut self.clear_button {
+			clear_button.reposition(y);
+		}
+	}
+
+	pub fn interact(&mut self, ui: &mut Ui) -> Response {
+		let mut response = self.button.interact(ui);
+		self.clear_response = self.clear_button.as_ref().map(|button| button.interact(ui));
+		let clear_clicked = self.clear_response.as_ref().is_some_and(Response::clicked);
+		self.datepicker.show_popup(ui, &mut response, clear_clicked);
+		response
+	}
+}
+
+impl<'a, 'b, R> Placed for LaidOutDatePicker<'a, 'b, R>
+where
+	R: RangeBounds<NaiveDateTime>,
+{
+	fn placed_rect(&self) -> Rect {
+		match &self.clear_button {
+			Some(clear_button) => self.button.placed_rect().union(clear_button.placed_rect()),
+			None => self.button.placed_rect(),
+		}
+	}
+
+	fn place(&mut self, rect: Rect) {
+		let diff = rect.min - self.placed_rect().min;
+		self.button.place(self.button.placed_rect().translate(diff));
+		if let Some(clear_button) = &mut self.clear_button {
+			clear_button.place(clear_button.placed_rect().translate(diff));
+		}
+	}
+}
+
+impl<'a, 'b, R> Paint<LaidOutDatePicker<'a, 'b, R>> for Ui
+where
+	R: RangeBounds<NaiveDateTime>,
+{
+	fn paint(&mut self, lodatepicker: &LaidOutDatePicker<'a, 'b, R>, response: &Response) {
+		self.paint(&lodatepicker.button, response);
+		if let (Some(clear_button), Some(clear_response)) =
+			(&lodatepicker.clear_button, &lodatepicker.clear_response)
+		{
+			self.paint(clear_button, clear_response);
+		}
+	}
 }
+
@@ -1,5 +1,6 @@
 use egui::{egui_assert, emath::*, Align, Direction};
 use std::f32::INFINITY;
+use std::ops::Range;
 
 // ----------------------------------------------------------------------------
 
@@ -693,16 +694,19 @@ impl Layout {
 							pos2(f32::NAN, frame_rect.max.y),
 						);
 					}
+					// New columns keep the column width, so that text keeps wrapping at it.
 					Direction::TopDown => {
+						let width = cursor.width().max(frame_rect.width());
 						*cursor = Rect::from_min_max(
 							pos2(frame_rect.min.x, f32::NAN),
-							pos2(frame_rect.max.x, INFINITY),
+							pos2(frame_rect.min.x + width, INFINITY),
 						);
 					}
 					Direction::BottomUp => {
+						let width = cursor.width().max(frame_rect.width());
 						*cursor = Rect::from_min_max(
 							pos2(frame_rect.min.x, -INFINITY),
-							pos2(frame_rect.max.x, f32::NAN),
+							pos2(frame_rect.min.x + width, f32::NAN),
 						);
 					}
 				};
@@ -758,6 +762,30 @@ impl Layout {
 		}
 	}
 
+	/// Move to the next column in a wrapping vertical layout.
+	/// Otherwise does nothing.
+	pub(crate) fn end_column(&mut self, region: &mut Region, spacing: Vec2) {
+		if self.main_wrap {
+			match self.main_dir {
+				Direction::TopDown => {
+					let new_left = region.cursor.right() + spacing.x;
+					region.cursor = Rect::from_min_max(
+						pos2(new_left, region.max_rect.top()),
+						pos2(new_left + region.cursor.width(), f32::INFINITY),
+					);
+				}
+				Direction::BottomUp => {
+					let new_left = region.cursor.right() + spacing.x;
+					region.cursor = Rect::from_min_max(
+						pos2(new_left, -f32::INFINITY),
+						pos2(new_left + region.cursor.width(), region.max_rect.bottom()),
+					);
+				}
+				Direction::LeftToRight | Direction::RightToLeft => {}
+			}
+		}
+	}
+
 	/// Set row height in horizontal wrapping layout.
 	pub(crate) fn set_row_height(&mut self, region: &mut Region, height: f32) {
 		if self.main_wrap && self.is_horizontal() {
@@ -768,6 +796,111 @@ impl Layout {
 
 // ----------------------------------------------------------------------------
 
+/// Break items of the given widths into rows no wider than `max_width`, starting a new row
+/// as soon as the next item does not fit.
+pub(crate) fn greedy_rows(widths: &[f32], spacing: f32, max_width: f32) -> Vec<Range<usize>> {
+	let mut rows = Vec::new();
+	let mut start = 0;
+	let mut width = 0.0;
+	for (i, &w) in widths.iter().enumerate() {
+		if i > start && width + spacing + w > max_width {
+			rows.push(start..i);
+			start = i;
+			width = w;
+		} else if i == start {
+			width = w;
+		} else {
+			width += spacing + w;
+		}
+	}
+	if start < widths.len() {
+		rows.push(start..widths.len());
+	}
+	rows
+}
+
+/// Break items of the given widths into rows no wider than `max_width`, minimizing the sum of
+/// the squared space left at the end of each row, in the manner of Knuth and Plass.
+///
+/// This gives rows of even length instead of full rows followed by a short one.
+/// Items wider than `max_width` get a row of their own.
+pub(crate) fn balanced_rows(widths: &[f32], spacing: f32, max_width: f32) -> Vec<Range<usize>> {
+	let n = widths.len();
+	// cost[i]: the least cost of breaking `widths[i..]`, break_at[i]: where its first row ends.
+	let mut cost = vec![0.0_f32; n + 1];
+	let mut break_at = vec![n; n + 1];
+	for i in (0..n).rev() {
+		cost[i] = f32::INFINITY;
+		let mut width = -spacing;
+		for (j, &w) in widths.iter().enumerate().skip(i) {
+			width += spacing + w;
+			if width > max_width && j > i {
+				break;
+			}
+			let slack = (max_width - width).max(0.0);
+			let total = slack * slack + cost[j + 1];
+			if total < cost[i] {
+				cost[i] = total;
+				break_at[i] = j + 1;
+			}
+		}
+	}
+
+	let mut rows = Vec::new();
+	let mut start = 0;
+	while start < n {
+		rows.push(start..break_at[start]);
+		start = break_at[start];
+	}
+	rows
+}
+
+/// Split items of the given heights, in order, into at most `columns` columns, minimizing the
+/// height of the tallest column, like the columns of a newspaper.
+pub(crate) fn balanced_columns(heights: &[f32], spacing: f32, columns: usize) -> Vec<Range<usize>> {
+	let n = heights.len();
+	let columns = columns.clamp(1, n.max(1));
+	// prefix[i]: the sum of `heights[..i]`
+	let prefix: Vec<f32> = std::iter::once(0.0)
+		.chain(heights.iter().scan(0.0, |sum, height| {
+			*sum += height;
+			Some(*sum)
+		}))
+		.collect();
+	let column_height =
+		|range: Range<usize>| prefix[range.end] - prefix[range.start] + spacing * range.len().saturating_sub(1) as f32;
+	// tallest[k][i]: the least height of the tallest column when `heights[i..]` is split into
+	// `k + 1` columns, break_at[k][i]: where its first column ends.
+	let mut tallest = vec![vec![f32::INFINITY; n + 1]; columns];
+	let mut break_at = vec![vec![n; n + 1]; columns];
+	for (i, height) in tallest[0].iter_mut().enumerate() {
+		*height = column_height(i..n);
+	}
+	for k in 1..columns {
+		for i in 0..=n {
+			for j in i..=n {
+				let height = column_height(i..j).max(tallest[k - 1][j]);
+				if height < tallest[k][i] {
+					tallest[k][i] = height;
+					break_at[k][i] = j;
+				}
+			}
+		}
+	}
+
+	let mut result = Vec::new();
+	let mut start = 0;
+	for k in (0..columns).rev() {
+		let end = break_at[k][start];
+		result.push(start..end);
+		start = end;
+	}
+	result.retain(|range| !range.is_empty());
+	result
+}
+
+// ----------------------------------------------------------------------------
+
 /// ## Debug stuff
 impl Layout {
 	/// Shows where the next widget is going to be placed
@@ -810,6 +943,19 @@ impl Layout {
 	}
 }
 
+impl From<Layout> for egui::Layout {
+	fn from(value: Layout) -> Self {
+		egui::Layout {
+			main_dir: value.main_dir,
+			main_wrap: value.main_wrap,
+			main_align: value.main_align,
+			main_justify: value.main_justify,
+			cross_align: value.cross_align,
+			cross_justify: value.cross_justify,
+		}
+	}
+}
+
 impl From<egui::Layout> for Layout {
 	fn from(value: egui::Layout) -> Self {
 		Layout {
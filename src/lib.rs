@@ -1,20 +1,31 @@
+pub mod calendar;
+pub mod constraint;
 pub mod datepicker;
+pub mod recurrence;
+pub mod responsive;
+pub mod size;
+pub mod stack;
+pub mod timegrid;
+pub mod timeline;
 mod grid;
 mod layout;
 mod placer;
+mod solver;
 
-use egui::{pos2, Align, Color32, Context, Direction, FontSelection, NumExt, Pos2, Rect, Response, Rounding, Sense, Shape, Style, TextureId, Ui, Vec2, WidgetText};
-use egui::style::TextStyle;
+use egui::{pos2, Align, Align2, Color32, Context, Direction, FontSelection, Frame, LayerId, NumExt, Painter, Pos2, Rect, Response, Rounding, Sense, Shape, Style, TextureId, Ui, Vec2, WidgetText};
+use egui::style::{Margin, TextStyle};
 use egui::widgets::Image;
 use egui::widget_text::WidgetTextGalley;
 use epaint::{Stroke, TextShape};
-use layout::Layout;
+pub use layout::Layout;
+use layout::{balanced_columns, balanced_rows, greedy_rows};
 use placer::Placer;
 
 pub struct WidgetPlacer<'ui> {
 	placer: Placer,
 	pub style: &'ui Style,
 	context: &'ui Context,
+	layer_id: LayerId,
 }
 
 impl<'ui> WidgetPlacer<'ui> {
@@ -23,6 +34,7 @@ impl<'ui> WidgetPlacer<'ui> {
 			placer: Placer::new(ui.available_rect_before_wrap(), ui.layout().clone().into()),
 			style: ui.style(),
 			context: ui.ctx(),
+			layer_id: ui.layer_id(),
 		}
 	}
 	/// Returns a [`Rect`] with exactly what you asked for.
@@ -30,6 +42,7 @@ impl<'ui> WidgetPlacer<'ui> {
 	/// The response rect will be larger if this is part of a justified layout or similar.
 	/// This means that if this is a narrow widget in a wide justified layout, then
 	/// the widget will react to interactions outside the returned [`Rect`].
+	#[track_caller]
 	pub fn allocate_exact_size(&mut self, desired_size: Vec2) -> (Rect, Rect) {
 		let response_rect = self.allocate_space(desired_size);
 		let rect = self
@@ -40,22 +53,29 @@ impl<'ui> WidgetPlacer<'ui> {
 
 	/// Reserve this much space and move the cursor.
 	/// Returns where to put the widget.
+	#[track_caller]
 	fn allocate_space(&mut self, desired_size: Vec2) -> Rect {
 		let item_spacing = self.style.spacing.item_spacing;
 		let frame_rect = self.placer.next_space(desired_size, item_spacing);
 		egui::egui_assert!(!frame_rect.any_nan());
 		let widget_rect = self.placer.justify_and_align(frame_rect, desired_size);
+		let max_rect = self.placer.max_rect();
 
 		self.placer
 			.advance_after_rects(frame_rect, widget_rect, item_spacing);
 
+		self.debug_paint_allocation(frame_rect, widget_rect, max_rect);
+
 		widget_rect
 	}
 
+	#[track_caller]
 	pub(crate) fn allocate_rect(&mut self, rect: Rect) {
 		egui::egui_assert!(!rect.any_nan());
 		let item_spacing = self.style.spacing.item_spacing;
+		let max_rect = self.placer.max_rect();
 		self.placer.advance_after_rects(rect, rect, item_spacing);
+		self.debug_paint_allocation(rect, rect, max_rect);
 	}
 
 	/// Where do we expect a zero-sized widget to be placed?
@@ -74,10 +94,129 @@ impl<'ui> WidgetPlacer<'ui> {
 		self.placer.advance_cursor(amount);
 	}
 
+	/// Move to the next row in a wrapping layout.
+	/// Otherwise does nothing.
+	pub fn end_row(&mut self) {
+		let painter = Painter::new(self.context.clone(), self.layer_id, self.placer.max_rect());
+		self.placer.end_row(self.style.spacing.item_spacing, &painter);
+	}
+
+	/// Move to the next column in a vertical wrapping layout, like [`ExtLayout::vertical_wrapped`].
+	/// Otherwise does nothing.
+	pub fn end_column(&mut self) {
+		self.placer.end_column(self.style.spacing.item_spacing);
+	}
+
+	/// The width of each of `columns` columns side by side in the available width.
+	fn column_width(&self, columns: usize) -> f32 {
+		let columns = columns.max(1) as f32;
+		let spacing = self.style.spacing.item_spacing.x;
+		((self.placer.available_rect_before_wrap().width() - (columns - 1.0) * spacing) / columns).at_least(0.0)
+	}
+
+	/// Re-align the widgets placed by this wrapping placer within their finished rows (or columns).
+	///
+	/// A row only gets as high as its highest widget once it is finished, so widgets placed
+	/// before that were aligned within a smaller height. This moves each widget to where
+	/// [`Layout::cross_align`] puts it in the finished row.
+	fn align_lines<W: Placed>(&self, widgets: &mut [W]) {
+		let layout = self.placer.layout();
+		if !layout.main_wrap() || self.placer.is_grid() {
+			return;
+		}
+		let lines: Vec<Rect> = self.placer.lines().collect();
+		for widget in widgets {
+			let rect = widget.placed_rect();
+			if layout.is_horizontal() {
+				let Some(line) = lines.iter().find(|line| line.y_range().contains(&rect.center().y)) else {
+					continue;
+				};
+				let y = layout.cross_align().align_size_within_range(rect.height(), line.y_range());
+				widget.place(rect.translate(Vec2::new(0.0, y.start() - rect.top())));
+			} else {
+				let Some(line) = lines.iter().find(|line| line.x_range().contains(&rect.center().x)) else {
+					continue;
+				};
+				let x = layout.cross_align().align_size_within_range(rect.width(), line.x_range());
+				widget.place(rect.translate(Vec2::new(x.start() - rect.left(), 0.0)));
+			}
+		}
+	}
+
+	/// Set the maximum width of the placer.
+	/// You won't be able to shrink it below the current minimum size.
+	pub fn set_max_width(&mut self, width: f32) {
+		self.placer.set_max_width(width);
+	}
+
+	/// Set the maximum height of the placer.
+	/// You won't be able to shrink it below the current minimum size.
+	pub fn set_max_height(&mut self, height: f32) {
+		self.placer.set_max_height(height);
+	}
+
+	/// Set the minimum width of the placer.
+	/// This can't shrink the placer, only make it larger.
+	pub fn set_min_width(&mut self, width: f32) {
+		self.placer.set_min_width(width);
+	}
+
+	/// Set the minimum height of the placer.
+	/// This can't shrink the placer, only make it larger.
+	pub fn set_min_height(&mut self, height: f32) {
+		self.placer.set_min_height(height);
+	}
+
+	/// Lay out the widgets added by `add_contents` in a child region of at least `desired_size`,
+	/// with its own layout. Either a [`Layout`] or an [`egui::Layout`] can be passed.
+	#[track_caller]
 	pub fn allocate_ui_with_layout<R>(
+		&mut self,
+		desired_size: Vec2,
+		layout: impl Into<Layout>,
+		add_contents: impl FnOnce(&mut Self) -> R,
+	) -> (R, Rect) {
+		self.allocate_child(desired_size, layout.into(), Margin::default(), add_contents)
+	}
+
+	/// Put the widgets added by `add_contents` in a [`Frame`] with a background, a border
+	/// and margins around them, using the layout of this placer.
+	///
+	/// Paint the returned [`LaidOutFrame`] before the widgets, so that it ends up behind them.
+	///
+	/// ```no_run
+	/// # use grus_gui_lib::{Create, Label, Paint, WidgetPlacer};
+	/// # fn draw(ui: &mut egui::Ui) {
+	/// let mut placer = WidgetPlacer::new(ui);
+	/// let frame = egui::Frame::group(placer.style).inner_margin(8.0);
+	/// let (label, background) = placer.frame(frame, |placer| placer.create(Label::new("Framed")));
+	/// let response = background.interact(ui);
+	/// ui.paint(&background, &response);
+	/// let response = label.interact(ui);
+	/// ui.paint(&label, &response);
+	/// # }
+	/// ```
+	#[track_caller]
+	pub fn frame<R>(&mut self, frame: Frame, add_contents: impl FnOnce(&mut Self) -> R) -> (R, LaidOutFrame) {
+		let desired_size = Vec2::new(self.placer.available_rect_before_wrap().width(), 0.0);
+		let layout = *self.placer.layout();
+		let margin = frame.inner_margin + frame.outer_margin;
+		let (ret, content_rect) = self.allocate_child(desired_size, layout, margin, add_contents);
+		let rect = Rect::from_min_max(
+			content_rect.min - frame.inner_margin.left_top(),
+			content_rect.max + frame.inner_margin.right_bottom(),
+		);
+		(ret, LaidOutFrame { rect, frame })
+	}
+
+	/// Lay out the widgets added by `add_contents` in a child placer, `margin` inside the space
+	/// allocated for it. Returns the rect of the contents, without the margin.
+	#[track_caller]
+	fn allocate_child<R>(
 		&mut self,
 		desired_size: Vec2,
 		layout: Layout,
+		margin: Margin,
 		add_contents: impl FnOnce(&mut Self) -> R,
 	) -> (R, Rect) {
 		egui::egui_assert!(desired_size.x >= 0.0 && desired_size.y >= 0.0);
@@ -85,15 +224,28 @@ impl<'ui> WidgetPlacer<'ui> {
 		let frame_rect = self.placer.next_space(desired_size, item_spacing);
 		let child_rect = self.placer.justify_and_align(frame_rect, desired_size);
 
+		let mut inner_rect = child_rect;
+		inner_rect.min += margin.left_top();
+		inner_rect.max -= margin.right_bottom();
+		// Make sure we don't shrink to the negative:
+		inner_rect.max.x = inner_rect.max.x.max(inner_rect.min.x);
+		inner_rect.max.y = inner_rect.max.y.max(inner_rect.min.y);
+
 		let mut child_wp = WidgetPlacer {
-			placer: Placer::new(child_rect, layout),
+			placer: Placer::new(inner_rect, layout),
 			style: self.style,
 			context: self.context,
+			layer_id: self.layer_id,
 		};
 		let ret = add_contents(&mut child_wp);
 		let final_child_rect = child_wp.placer.min_rect();
+		child_wp.debug_paint_child();
 
-		self.placer.advance_after_rects(final_child_rect, final_child_rect, item_spacing);
+		let outer_rect = Rect::from_min_max(
+			final_child_rect.min - margin.left_top(),
+			final_child_rect.max + margin.right_bottom(),
+		);
+		self.placer.advance_after_rects(outer_rect, outer_rect, item_spacing);
 
 		(ret, final_child_rect)
 	}
@@ -135,9 +287,19 @@ impl<'ui> WidgetPlacer<'ui> {
 		}
 	}
 
+	/// Is the layout debug overlay on?
+	///
+	/// It is toggled with [`egui::style::DebugOptions::debug_on_hover`], e.g. through
+	/// [`Context::set_debug_on_hover`].
+	pub fn debug_on_hover(&self) -> bool {
+		self.style.debug.debug_on_hover
+	}
+
 	/// Should text wrap in this [`Ui`]?
 	///
 	/// This is determined first by [`Style::wrap`], and then by the layout of this [`Ui`].
+	/// In vertical wrapping layouts, like [`ExtLayout::vertical_wrapped`], text wraps at the
+	/// width of the current column.
 	pub fn wrap_text(&self) -> bool {
 		if let Some(wrap) = self.style.wrap {
 			wrap
@@ -150,26 +312,332 @@ impl<'ui> WidgetPlacer<'ui> {
 	}
 }
 
+/// ## Debug stuff
+impl WidgetPlacer<'_> {
+	fn is_pointer_in(&self, rect: Rect) -> bool {
+		self.context.pointer_hover_pos().is_some_and(|pos| rect.contains(pos))
+	}
+
+	/// Paints a widget allocation when debugging: the frame rect in blue, the widget rect in red
+	/// and, while hovered, where the widget was created and where the next one goes.
+	///
+	/// Overflowing `max_rect` is also shown when [`egui::style::DebugOptions::show_expand_width`]
+	/// or [`egui::style::DebugOptions::show_expand_height`] is set.
+	#[track_caller]
+	fn debug_paint_allocation(&self, frame_rect: Rect, widget_rect: Rect, max_rect: Rect) {
+		let debug = &self.style.debug;
+		let too_wide = widget_rect.min.x < max_rect.min.x - 0.5 || widget_rect.max.x > max_rect.max.x + 0.5;
+		let too_high = widget_rect.min.y < max_rect.min.y - 0.5 || widget_rect.max.y > max_rect.max.y + 0.5;
+		let show_width = (debug.debug_on_hover || debug.show_expand_width) && too_wide;
+		let show_height = (debug.debug_on_hover || debug.show_expand_height) && too_high;
+		if !debug.debug_on_hover && !show_width && !show_height {
+			return;
+		}
+
+		let painter = self.context.debug_painter();
+		let hovered = self.is_pointer_in(widget_rect);
+		if debug.debug_on_hover {
+			let width = if hovered { 1.0 } else { 0.5 };
+			painter.rect_stroke(frame_rect, 0.0, (width, Color32::LIGHT_BLUE));
+			painter.rect_stroke(widget_rect, 0.0, (width, Color32::RED));
+		}
+
+		let stroke = Stroke::new(2.5, Color32::from_rgb(200, 0, 0));
+		let paint_line_seg = |a, b| painter.line_segment([a, b], stroke);
+		if show_width {
+			paint_line_seg(widget_rect.left_top(), widget_rect.left_bottom());
+			paint_line_seg(widget_rect.left_center(), widget_rect.right_center());
+			paint_line_seg(widget_rect.right_top(), widget_rect.right_bottom());
+		}
+		if show_height {
+			paint_line_seg(widget_rect.left_top(), widget_rect.right_top());
+			paint_line_seg(widget_rect.center_top(), widget_rect.center_bottom());
+			paint_line_seg(widget_rect.left_bottom(), widget_rect.right_bottom());
+		}
+
+		if debug.debug_on_hover && hovered {
+			let location = std::panic::Location::caller();
+			let mut text = format!(
+				"{}:{}\n{:.1} x {:.1}",
+				location.file(),
+				location.line(),
+				widget_rect.width(),
+				widget_rect.height(),
+			);
+			if too_wide || too_high {
+				text.push_str("\noverflows max_rect");
+			}
+			painter.debug_text(widget_rect.left_bottom(), Align2::LEFT_TOP, Color32::RED, text);
+			self.placer.debug_paint_cursor(&painter, "next");
+		}
+	}
+
+	/// Creates the widgets of `add_contents` in a placer only used to measure them, before they
+	/// are placed elsewhere. Its allocations are not painted when debugging, as they would not
+	/// match where the widgets end up.
+	fn measure<W>(&self, rect: Rect, layout: Layout, add_contents: impl FnOnce(&mut WidgetPlacer) -> Vec<W>) -> Vec<W> {
+		let debug = &self.style.debug;
+		let quiet_style;
+		let style = if debug.debug_on_hover || debug.show_expand_width || debug.show_expand_height {
+			let mut style = self.style.clone();
+			style.debug = Default::default();
+			quiet_style = style;
+			&quiet_style
+		} else {
+			self.style
+		};
+		let mut child_wp = WidgetPlacer {
+			placer: Placer::new(rect, layout),
+			style,
+			context: self.context,
+			layer_id: self.layer_id,
+		};
+		add_contents(&mut child_wp)
+	}
+
+	/// Paints the `min_rect` (green) and `max_rect` (yellow) of a child placer when debugging,
+	/// and its cursor while hovered.
+	#[track_caller]
+	fn debug_paint_child(&self) {
+		if !self.debug_on_hover() {
+			return;
+		}
+		let painter = self.context.debug_painter();
+		let min_rect = self.placer.min_rect();
+		let max_rect = self.placer.max_rect();
+		painter.rect_stroke(max_rect, 0.0, (1.0, Color32::YELLOW));
+		painter.rect_stroke(min_rect, 0.0, (1.0, Color32::GREEN));
+		if self.is_pointer_in(max_rect) && !self.is_pointer_in(min_rect) {
+			let location = std::panic::Location::caller();
+			let text = format!("{}:{}\nchild {:?}", location.file(), location.line(), self.placer.layout().main_dir());
+			painter.debug_text(max_rect.left_top(), Align2::LEFT_BOTTOM, Color32::YELLOW, text);
+			self.placer.debug_paint_cursor(&painter, "next");
+		}
+	}
+}
+
+/// Shorthands for laying out widgets in a child region, like [`Ui::horizontal`] and friends.
+///
+/// ```no_run
+/// # use grus_gui_lib::{Button, Checkbox, Create, ExtLayout, Label, Layout, WidgetPlacer, Wrap};
+/// # fn draw(ui: &mut egui::Ui) {
+/// let mut placer = WidgetPlacer::new(ui);
+/// placer.vertical(|placer| {
+///     let title = placer.create(Label::new("Title"));
+///     let (labels, _rect) = placer.horizontal_wrapped(|placer| {
+///         ["one", "two", "three"].map(|text| placer.create(Label::new(text))).into()
+///     });
+/// });
+/// placer.with_layout(Layout::top_down_justified(egui::Align::Center), |placer| {
+///     placer.create(Label::new("centered"))
+/// });
+/// let (legend, _rect) = placer.columns_balanced(3, |placer| {
+///     (1..=10).map(|i| placer.create(Checkbox::new(i % 2 == 0, format!("Series {i}")))).collect()
+/// });
+/// let (chips, _rect) = placer.wrapped(Wrap::balanced().justify(true), |placer| {
+///     ["rust", "egui", "layout", "chips", "tags"].map(|tag| placer.create(Button::new(tag))).into()
+/// });
+/// # }
+/// ```
 pub trait ExtLayout {
+	/// Place widgets left to right, vertically centered, in a row as high as a button.
 	fn left_to_right<R>(&mut self, add_contents: impl FnOnce(&mut WidgetPlacer) -> R) -> (R, Rect);
+	/// Place widgets right to left, vertically centered, in a row as high as a button.
 	fn right_to_left<R>(&mut self, add_contents: impl FnOnce(&mut WidgetPlacer) -> R) -> (R, Rect);
+	/// Place the returned widgets left to right, wrapping to a new row when running out of width
+	/// or at [`WidgetPlacer::end_row`].
+	///
+	/// Once the rows are finished the returned widgets are moved to the vertical center of their
+	/// row, so a taller widget late in a row doesn't leave the earlier ones misaligned.
+	fn horizontal_wrapped<W: Placed>(&mut self, add_contents: impl FnOnce(&mut WidgetPlacer) -> Vec<W>) -> (Vec<W>, Rect);
+	/// Place widgets top down, left aligned.
+	fn vertical<R>(&mut self, add_contents: impl FnOnce(&mut WidgetPlacer) -> R) -> (R, Rect);
+	/// Place widgets top down, horizontally centered.
+	fn vertical_centered<R>(&mut self, add_contents: impl FnOnce(&mut WidgetPlacer) -> R) -> (R, Rect);
+	/// Place widgets with the given layout, using all of the available width.
+	fn with_layout<R>(&mut self, layout: impl Into<Layout>, add_contents: impl FnOnce(&mut WidgetPlacer) -> R) -> (R, Rect);
+	/// Place widgets top down in columns as wide as `columns` columns side by side,
+	/// starting a new column when running out of height or at [`WidgetPlacer::end_column`].
+	///
+	/// Text wraps at the column width. `columns` only sets the width of the columns: contents not
+	/// fitting in `columns` columns continue in more columns, past the available width.
+	/// Like in [`Self::horizontal_wrapped`], the returned widgets are re-aligned in their
+	/// finished columns.
+	fn vertical_wrapped<W: Placed>(&mut self, columns: usize, add_contents: impl FnOnce(&mut WidgetPlacer) -> Vec<W>) -> (Vec<W>, Rect);
+	/// Place the returned widgets top down in `columns` columns of about equal height,
+	/// like the columns of a newspaper.
+	///
+	/// The widgets are measured first and placed afterwards, in the order they are returned.
+	fn columns_balanced<W: Placed>(&mut self, columns: usize, add_contents: impl FnOnce(&mut WidgetPlacer) -> Vec<W>) -> (Vec<W>, Rect);
+	/// Place the returned widgets left to right in rows, broken as told by [`Wrap`].
+	///
+	/// Unlike [`Self::horizontal_wrapped`] the widgets are measured first and placed afterwards,
+	/// so the rows can be balanced and justified.
+	fn wrapped<W: Placed>(&mut self, wrap: Wrap, add_contents: impl FnOnce(&mut WidgetPlacer) -> Vec<W>) -> (Vec<W>, Rect);
+}
+
+/// How [`ExtLayout::wrapped`] breaks widgets into rows.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Wrap {
+	balanced: bool,
+	justify: bool,
+}
+
+impl Wrap {
+	/// Fill each row before starting the next one, like [`ExtLayout::horizontal_wrapped`].
+	pub fn greedy() -> Self {
+		Wrap { balanced: false, justify: false }
+	}
+
+	/// Choose the row breaks so that the rows are about equally long.
+	pub fn balanced() -> Self {
+		Wrap { balanced: true, justify: false }
+	}
+
+	/// Spread the widgets of all rows but the last over the full width.
+	pub fn justify(mut self, justify: bool) -> Self {
+		self.justify = justify;
+		self
+	}
 }
 
 impl ExtLayout for WidgetPlacer<'_> {
+	#[track_caller]
 	fn left_to_right<R>(&mut self, add_contents: impl FnOnce(&mut WidgetPlacer) -> R) -> (R, Rect) {
 		let initial_size = Vec2::new(
 			self.placer.available_rect_before_wrap().size().x,
 			self.style.spacing.interact_size.y,
 		);
-		self.allocate_ui_with_layout(initial_size, Layout::left_to_right(Align::Center).into(), add_contents)
+		self.allocate_ui_with_layout(initial_size, Layout::left_to_right(Align::Center), add_contents)
 	}
 
+	#[track_caller]
 	fn right_to_left<R>(&mut self, add_contents: impl FnOnce(&mut WidgetPlacer) -> R) -> (R, Rect) {
 		let initial_size = Vec2::new(
 			self.placer.available_rect_before_wrap().size().x,
 			self.style.spacing.interact_size.y,
 		);
-		self.allocate_ui_with_layout(initial_size, Layout::right_to_left(Align::Center).into(), add_contents)
+		self.allocate_ui_with_layout(initial_size, Layout::right_to_left(Align::Center), add_contents)
+	}
+
+	#[track_caller]
+	fn horizontal_wrapped<W: Placed>(&mut self, add_contents: impl FnOnce(&mut WidgetPlacer) -> Vec<W>) -> (Vec<W>, Rect) {
+		let initial_size = Vec2::new(
+			self.placer.available_rect_before_wrap().size().x,
+			self.style.spacing.interact_size.y,
+		);
+		let layout = Layout::left_to_right(Align::Center).with_main_wrap(true);
+		self.allocate_ui_with_layout(initial_size, layout, |placer| {
+			let mut widgets = add_contents(placer);
+			placer.align_lines(&mut widgets);
+			widgets
+		})
+	}
+
+	#[track_caller]
+	fn vertical<R>(&mut self, add_contents: impl FnOnce(&mut WidgetPlacer) -> R) -> (R, Rect) {
+		self.with_layout(Layout::top_down(Align::Min), add_contents)
+	}
+
+	#[track_caller]
+	fn vertical_centered<R>(&mut self, add_contents: impl FnOnce(&mut WidgetPlacer) -> R) -> (R, Rect) {
+		self.with_layout(Layout::top_down(Align::Center), add_contents)
+	}
+
+	#[track_caller]
+	fn with_layout<R>(&mut self, layout: impl Into<Layout>, add_contents: impl FnOnce(&mut WidgetPlacer) -> R) -> (R, Rect) {
+		let initial_size = Vec2::new(self.placer.available_rect_before_wrap().size().x, 0.0);
+		self.allocate_ui_with_layout(initial_size, layout, add_contents)
+	}
+
+	#[track_caller]
+	fn vertical_wrapped<W: Placed>(&mut self, columns: usize, add_contents: impl FnOnce(&mut WidgetPlacer) -> Vec<W>) -> (Vec<W>, Rect) {
+		let column_width = self.column_width(columns);
+		let initial_size = self.placer.available_rect_before_wrap().size();
+		let layout = Layout::top_down(Align::Min).with_main_wrap(true);
+		self.allocate_ui_with_layout(initial_size, layout, |placer| {
+			let mut cursor = placer.placer.cursor();
+			cursor.max.x = cursor.min.x + column_width;
+			placer.placer.set_cursor(cursor);
+			let mut widgets = add_contents(placer);
+			placer.align_lines(&mut widgets);
+			widgets
+		})
+	}
+
+	#[track_caller]
+	fn columns_balanced<W: Placed>(&mut self, columns: usize, add_contents: impl FnOnce(&mut WidgetPlacer) -> Vec<W>) -> (Vec<W>, Rect) {
+		let item_spacing = self.style.spacing.item_spacing;
+		let available = self.placer.available_rect_before_wrap();
+		let max_rect = self.placer.max_rect();
+		let column_width = self.column_width(columns);
+
+		// Measure the widgets in a single column first.
+		let measure_rect = Rect::from_min_size(available.min, Vec2::new(column_width, available.height()));
+		let mut widgets = self.measure(measure_rect, Layout::top_down(Align::Min), add_contents);
+		let sizes: Vec<Vec2> = widgets.iter().map(|widget| widget.placed_rect().size()).collect();
+		let heights: Vec<f32> = sizes.iter().map(|size| size.y).collect();
+
+		let mut rect = Rect::from_min_size(available.min, Vec2::ZERO);
+		for (column, range) in balanced_columns(&heights, item_spacing.y, columns).into_iter().enumerate() {
+			let mut pos = available.min + Vec2::new(column as f32 * (column_width + item_spacing.x), 0.0);
+			for i in range {
+				let widget_rect = Rect::from_min_size(pos, sizes[i]);
+				widgets[i].place(widget_rect);
+				self.debug_paint_allocation(widget_rect, widget_rect, max_rect);
+				rect = rect.union(widget_rect);
+				pos.y += sizes[i].y + item_spacing.y;
+			}
+		}
+
+		self.placer.advance_after_rects(rect, rect, item_spacing);
+
+		(widgets, rect)
+	}
+
+	#[track_caller]
+	fn wrapped<W: Placed>(&mut self, wrap: Wrap, add_contents: impl FnOnce(&mut WidgetPlacer) -> Vec<W>) -> (Vec<W>, Rect) {
+		let item_spacing = self.style.spacing.item_spacing;
+		let available = self.placer.available_rect_before_wrap();
+		let max_rect = self.placer.max_rect();
+
+		// Measure the widgets in a single row first.
+		let mut widgets = self.measure(available, Layout::left_to_right(Align::Center), add_contents);
+		let sizes: Vec<Vec2> = widgets.iter().map(|widget| widget.placed_rect().size()).collect();
+		let widths: Vec<f32> = sizes.iter().map(|size| size.x).collect();
+
+		let rows = if wrap.balanced {
+			balanced_rows(&widths, item_spacing.x, available.width())
+		} else {
+			greedy_rows(&widths, item_spacing.x, available.width())
+		};
+
+		let mut rect = Rect::from_min_size(available.min, Vec2::ZERO);
+		let mut top = available.top();
+		let row_count = rows.len();
+		for (row_index, row) in rows.into_iter().enumerate() {
+			let row_height = sizes[row.clone()].iter().map(|size| size.y).fold(0.0, f32::max);
+			let gap = if wrap.justify && row_index + 1 < row_count && row.len() > 1 {
+				let content_width: f32 = widths[row.clone()].iter().sum();
+				((available.width() - content_width) / (row.len() - 1) as f32).max(item_spacing.x)
+			} else {
+				item_spacing.x
+			};
+			let mut x = available.left();
+			for i in row {
+				let size = sizes[i];
+				let widget_rect = Rect::from_min_size(pos2(x, top + 0.5 * (row_height - size.y)), size);
+				widgets[i].place(widget_rect);
+				self.debug_paint_allocation(widget_rect, widget_rect, max_rect);
+				rect = rect.union(widget_rect);
+				x += size.x + gap;
+			}
+			top += row_height + item_spacing.y;
+		}
+
+		self.placer.advance_after_rects(rect, rect, item_spacing);
+
+		(widgets, rect)
 	}
 }
 
@@ -421,6 +889,7 @@ pub trait Create<W> {
 
 impl Create<Label> for WidgetPlacer<'_> {
 	type LaidOutWidget = LaidOutLabel;
+	#[track_caller]
 	fn create(&mut self, label: Label) -> LaidOutLabel {
 		let sense = label.sense.unwrap_or_else(|| {
 			// We only want to focus labels if the screen reader is on.
@@ -451,6 +920,7 @@ impl Create<Label> for WidgetPlacer<'_> {
 			.into_text_job(self.style, FontSelection::Default, valign);
 
 		let should_wrap = label.wrap.unwrap_or_else(|| self.wrap_text());
+		// In a vertical wrapping layout this is the width of the current column.
 		let available_width = self.placer.available_size().x;
 
 		if should_wrap
@@ -520,6 +990,7 @@ impl Create<Label> for WidgetPlacer<'_> {
 
 impl Create<Button> for WidgetPlacer<'_> {
 	type LaidOutWidget = LaidOutButton;
+	#[track_caller]
 	fn create(&mut self, button: Button) -> LaidOutButton {
 		let Button {
 			text,
@@ -577,6 +1048,7 @@ impl Create<Button> for WidgetPlacer<'_> {
 
 impl Create<Checkbox> for WidgetPlacer<'_> {
 	type LaidOutWidget = LaidOutCheckbox;
+	#[track_caller]
 	fn create(&mut self, checkbox: Checkbox) -> LaidOutCheckbox {
 		let Checkbox { checked, text } = checkbox;
 
@@ -608,6 +1080,7 @@ impl Create<Checkbox> for WidgetPlacer<'_> {
 
 impl Create<RadioButton> for WidgetPlacer<'_> {
 	type LaidOutWidget = LaidOutRadioButton;
+	#[track_caller]
 	fn create(&mut self, radio: RadioButton) -> LaidOutRadioButton {
 		let RadioButton { checked, text } = radio;
 
@@ -735,6 +1208,97 @@ impl LaidOutRadioButton {
 	}
 }
 
+/// The background of a [`WidgetPlacer::frame`].
+pub struct LaidOutFrame {
+	rect: Rect,
+	frame: Frame,
+}
+
+impl LaidOutFrame {
+	pub fn reposition(&mut self, y: f32) {
+		self.rect = self.rect.translate(Vec2::new(0., y - self.rect.top()));
+	}
+
+	pub fn interact(&self, ui: &mut Ui) -> Response {
+		let response = ui.interact(self.rect, ui.next_auto_id(), Sense::hover());
+		ui.skip_ahead_auto_ids(1);
+		response
+	}
+}
+
+/// A laid out widget that can be moved, e.g. by a [`constraint::ConstraintLayout`],
+/// after it was created and before it is interacted with.
+pub trait Placed {
+	/// The rect the widget occupies.
+	fn placed_rect(&self) -> Rect;
+	/// Move the widget to `rect`. Widgets that cannot be resized keep their size.
+	fn place(&mut self, rect: Rect);
+}
+
+impl<P: Placed + ?Sized> Placed for Box<P> {
+	fn placed_rect(&self) -> Rect {
+		(**self).placed_rect()
+	}
+
+	fn place(&mut self, rect: Rect) {
+		(**self).place(rect);
+	}
+}
+
+impl Placed for LaidOutFrame {
+	fn placed_rect(&self) -> Rect {
+		self.rect
+	}
+
+	fn place(&mut self, rect: Rect) {
+		self.rect = rect;
+	}
+}
+
+impl Placed for LaidOutLabel {
+	fn placed_rect(&self) -> Rect {
+		self.response_rect
+	}
+
+	fn place(&mut self, rect: Rect) {
+		let diff = rect.min - self.response_rect.min;
+		self.pos += diff;
+		self.response_rect = self.response_rect.translate(diff);
+	}
+}
+
+impl Placed for LaidOutButton {
+	fn placed_rect(&self) -> Rect {
+		self.rect
+	}
+
+	fn place(&mut self, rect: Rect) {
+		self.rect = rect;
+	}
+}
+
+impl Placed for LaidOutCheckbox {
+	fn placed_rect(&self) -> Rect {
+		self.response_rect
+	}
+
+	fn place(&mut self, rect: Rect) {
+		self.rect = self.rect.translate(rect.min - self.response_rect.min);
+		self.response_rect = self.response_rect.translate(rect.min - self.response_rect.min);
+	}
+}
+
+impl Placed for LaidOutRadioButton {
+	fn placed_rect(&self) -> Rect {
+		self.response_rect
+	}
+
+	fn place(&mut self, rect: Rect) {
+		self.rect = self.rect.translate(rect.min - self.response_rect.min);
+		self.response_rect = self.response_rect.translate(rect.min - self.response_rect.min);
+	}
+}
+
 pub trait Paint<W> {
 	fn paint(&mut self, lowidget: &W, response: &Response);
 }
@@ -832,6 +1396,14 @@ impl Paint<LaidOutButton> for Ui {
 	}
 }
 
+impl Paint<LaidOutFrame> for Ui {
+	fn paint(&mut self, loframe: &LaidOutFrame, _response: &Response) {
+		if self.is_rect_visible(loframe.rect) {
+			self.painter().add(loframe.frame.paint(loframe.rect));
+		}
+	}
+}
+
 impl Paint<LaidOutCheckbox> for Ui {
 	fn paint(&mut self, locheckbox: &LaidOutCheckbox, response: &Response) {
 		let &LaidOutCheckbox { rect, checked, ref text, icon_width, icon_spacing, .. } = locheckbox;
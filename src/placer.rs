@@ -7,6 +7,10 @@ pub(crate) struct Placer {
 	grid: Option<grid::GridLayout>,
 	layout: Layout,
 	region: Region,
+	/// The finished rows (or columns) of a wrapping layout.
+	lines: Vec<Rect>,
+	/// The frames of the current row (or column) of a wrapping layout.
+	line: Rect,
 }
 
 impl Placer {
@@ -16,6 +20,8 @@ impl Placer {
 			grid: None,
 			layout,
 			region,
+			lines: Vec::new(),
+			line: Rect::NOTHING,
 		}
 	}
 
@@ -167,6 +173,15 @@ impl Placer {
 		if let Some(grid) = &mut self.grid {
 			grid.advance(&mut self.region.cursor, frame_rect, widget_rect);
 		} else {
+			if self.layout.main_wrap() {
+				// Same test as the layout uses for starting a new row or column:
+				if self.region.cursor.intersects(frame_rect.shrink(1.0)) || self.line == Rect::NOTHING {
+					self.line = self.line.union(frame_rect);
+				} else {
+					self.lines.push(self.line);
+					self.line = frame_rect;
+				}
+			}
 			self.layout.advance_after_rects(
 				&mut self.region.cursor,
 				frame_rect,
@@ -187,6 +202,28 @@ impl Placer {
 			grid.end_row(&mut self.region.cursor, painter);
 		} else {
 			self.layout.end_row(&mut self.region, item_spacing);
+			if self.line != Rect::NOTHING {
+				self.lines.push(self.line);
+				self.line = Rect::NOTHING;
+			}
+		}
+	}
+
+	/// The rows (or columns) of a wrapping layout so far, each covering the frames placed in it.
+	pub(crate) fn lines(&self) -> impl Iterator<Item = Rect> + '_ {
+		let line = (self.line != Rect::NOTHING).then_some(self.line);
+		self.lines.iter().copied().chain(line)
+	}
+
+	/// Move to the next column in a vertical wrapping layout.
+	/// Otherwise does nothing.
+	pub(crate) fn end_column(&mut self, item_spacing: Vec2) {
+		if self.grid.is_none() {
+			self.layout.end_column(&mut self.region, item_spacing);
+			if self.line != Rect::NOTHING {
+				self.lines.push(self.line);
+				self.line = Rect::NOTHING;
+			}
 		}
 	}
 
@@ -0,0 +1,579 @@
+//! Repeating events described by iCalendar recurrence rules (RRULE, see RFC 5545), and an editor
+//! for them, which previews the occurrences in a [`Calendar`].
+//!
+//! ```no_run
+//! use chrono::prelude::*;
+//! use eframe::egui::Ui;
+//! use grus_gui_lib::recurrence::{RecurrenceEditor, RecurrenceRule};
+//!
+//! fn draw_schedule(ui: &mut Ui, start: &mut NaiveDateTime, rule: &mut RecurrenceRule) {
+//!     if ui.add(RecurrenceEditor::new("meeting_rule", rule, start)).changed() {
+//!         println!("RRULE:{rule}");
+//!     }
+//! }
+//! ```
+
+use core::fmt;
+use core::ops::{RangeFrom, RangeFull};
+use core::str::FromStr;
+use std::collections::BTreeSet;
+use std::hash::Hash;
+
+use chrono::naive::NaiveDateTime;
+use chrono::{prelude::*, Days, Duration, Months};
+use eframe::{
+	egui,
+	egui::{Color32, DragValue, Id, Response, Ui, Widget},
+};
+
+use crate::calendar::{Calendar, DayDecoration, Locale};
+use crate::datepicker::DatePicker;
+
+/// How often an event repeats.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Frequency {
+	Daily,
+	Weekly,
+	Monthly,
+	Yearly,
+}
+
+impl Frequency {
+	const ALL: [Frequency; 4] = [Frequency::Daily, Frequency::Weekly, Frequency::Monthly, Frequency::Yearly];
+
+	fn keyword(self) -> &'static str {
+		match self {
+			Frequency::Daily => "DAILY",
+			Frequency::Weekly => "WEEKLY",
+			Frequency::Monthly => "MONTHLY",
+			Frequency::Yearly => "YEARLY",
+		}
+	}
+
+	fn unit(self, locale: &Locale) -> &'static str {
+		let index = match self {
+			Frequency::Daily => 0,
+			Frequency::Weekly => 1,
+			Frequency::Monthly => 2,
+			Frequency::Yearly => 3,
+		};
+		locale.frequency_units[index]
+	}
+}
+
+/// When a repeating event stops.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecurrenceEnd {
+	Never,
+	/// After this many occurrences, `COUNT` in a RRULE.
+	Count(u32),
+	/// With the last occurrence at or before this date, `UNTIL` in a RRULE.
+	Until(NaiveDateTime),
+}
+
+/// The subset of an iCalendar RRULE made of `FREQ`, `INTERVAL`, `BYDAY` without ordinals, and
+/// `COUNT` or `UNTIL`.
+///
+/// Rules are parsed with [`str::parse`] and serialized with [`ToString::to_string`], with or
+/// without the leading `RRULE:`.
+///
+/// ```
+/// # use chrono::prelude::*;
+/// # use grus_gui_lib::recurrence::RecurrenceRule;
+/// let rule: RecurrenceRule = "RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;COUNT=3".parse().unwrap();
+/// let start = NaiveDate::from_ymd_opt(2023, 5, 1).unwrap().and_hms_opt(9, 0, 0).unwrap();
+/// let days: Vec<_> = rule.occurrences(start).map(|date| date.day()).collect();
+/// assert_eq!(days, [1, 3, 15]);
+/// assert_eq!(rule.to_string(), "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;COUNT=3");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecurrenceRule {
+	pub frequency: Frequency,
+	/// Repeat every `interval` days, weeks, months or years.
+	pub interval: u32,
+	/// Only repeat on these weekdays. Weekly rules without weekdays repeat on the weekday of
+	/// the start, monthly and yearly rules with weekdays repeat on every such day of the month
+	/// or year.
+	pub by_day: Vec<Weekday>,
+	pub end: RecurrenceEnd,
+}
+
+impl Default for RecurrenceRule {
+	fn default() -> Self {
+		Self::new(Frequency::Weekly)
+	}
+}
+
+impl RecurrenceRule {
+	pub fn new(frequency: Frequency) -> Self {
+		Self {
+			frequency,
+			interval: 1,
+			by_day: Vec::new(),
+			end: RecurrenceEnd::Never,
+		}
+	}
+
+	pub fn interval(mut self, interval: u32) -> Self {
+		self.interval = interval.max(1);
+		self
+	}
+
+	pub fn by_day(mut self, weekdays: impl IntoIterator<Item = Weekday>) -> Self {
+		self.by_day = weekdays.into_iter().collect();
+		self
+	}
+
+	pub fn end(mut self, end: RecurrenceEnd) -> Self {
+		self.end = end;
+		self
+	}
+
+	/// The most periods [`Self::occurrences`] looks at, so that it ends for rules never matching.
+	const MAX_PERIODS: u32 = 100_000;
+
+	/// The occurrences of an event starting at `start`, in ascending order.
+	///
+	/// `start` itself is only included if it matches the rule. Without an end, the iterator stops
+	/// after 100 000 days, weeks, months or years, so limit it with e.g. [`Iterator::take_while`],
+	/// or use [`Self::occurrences_until`].
+	pub fn occurrences(&self, start: NaiveDateTime) -> impl Iterator<Item = NaiveDateTime> + '_ {
+		self.occurrences_in_periods(start, NaiveDate::MAX)
+	}
+
+	/// The occurrences of an event starting at `start`, up to and including the day `until`.
+	///
+	/// Unlike limiting [`Self::occurrences`], this stops at `until` even if the rule matches no day.
+	pub fn occurrences_until(&self, start: NaiveDateTime, until: NaiveDate) -> impl Iterator<Item = NaiveDateTime> + '_ {
+		self.occurrences_in_periods(start, until).take_while(move |date| date.date() <= until)
+	}
+
+	/// The occurrences in the periods starting at or before `last_period_start`.
+	fn occurrences_in_periods(
+		&self,
+		start: NaiveDateTime,
+		last_period_start: NaiveDate,
+	) -> impl Iterator<Item = NaiveDateTime> + '_ {
+		let count = match self.end {
+			RecurrenceEnd::Count(count) => count as usize,
+			_ => usize::MAX,
+		};
+		let until = match self.end {
+			RecurrenceEnd::Until(until) => Some(until),
+			_ => None,
+		};
+		(0..Self::MAX_PERIODS)
+			.map_while(move |period| {
+				self.period_start(start.date(), period)
+					.filter(|period_start| *period_start <= last_period_start)
+			})
+			.flat_map(move |period_start| self.period_days(start.date(), period_start))
+			.map(move |day| day.and_time(start.time()))
+			.filter(move |date| *date >= start)
+			.take_while(move |date| until.map_or(true, |until| *date <= until))
+			.take(count)
+	}
+
+	/// The first day of the `period`th day, week, month or year the event repeats in.
+	fn period_start(&self, start: NaiveDate, period: u32) -> Option<NaiveDate> {
+		let steps = period.checked_mul(self.interval.max(1))?;
+		match self.frequency {
+			Frequency::Daily => start.checked_add_days(Days::new(steps.into())),
+			Frequency::Weekly => {
+				let monday = start - Duration::days(start.weekday().num_days_from_monday().into());
+				monday.checked_add_days(Days::new(u64::from(steps) * 7))
+			}
+			Frequency::Monthly => start.with_day(1)?.checked_add_months(Months::new(steps)),
+			Frequency::Yearly => start.with_ordinal(1)?.checked_add_months(Months::new(steps.checked_mul(12)?)),
+		}
+	}
+
+	/// The days the event occurs on in the period starting at `period_start`.
+	fn period_days(&self, start: NaiveDate, period_start: NaiveDate) -> Vec<NaiveDate> {
+		let matches = |day: &NaiveDate| self.by_day.is_empty() || self.by_day.contains(&day.weekday());
+		match self.frequency {
+			Frequency::Daily => Some(period_start).into_iter().filter(matches).collect(),
+			Frequency::Weekly => {
+				let weekdays = if self.by_day.is_empty() { vec![start.weekday()] } else { self.by_day.clone() };
+				period_start
+					.iter_days()
+					.take(7)
+					.filter(|day| weekdays.contains(&day.weekday()))
+					.collect()
+			}
+			Frequency::Monthly if self.by_day.is_empty() => period_start.with_day(start.day()).into_iter().collect(),
+			Frequency::Yearly if self.by_day.is_empty() => {
+				NaiveDate::from_ymd_opt(period_start.year(), start.month(), start.day()).into_iter().collect()
+			}
+			Frequency::Monthly => period_start
+				.iter_days()
+				.take_while(|day| day.month() == period_start.month())
+				.filter(matches)
+				.collect(),
+			Frequency::Yearly => period_start
+				.iter_days()
+				.take_while(|day| day.year() == period_start.year())
+				.filter(matches)
+				.collect(),
+		}
+	}
+}
+
+impl fmt::Display for RecurrenceRule {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "FREQ={}", self.frequency.keyword())?;
+		if self.interval > 1 {
+			write!(f, ";INTERVAL={}", self.interval)?;
+		}
+		if !self.by_day.is_empty() {
+			let days: Vec<_> = self.by_day.iter().map(|weekday| weekday_code(*weekday)).collect();
+			write!(f, ";BYDAY={}", days.join(","))?;
+		}
+		match self.end {
+			RecurrenceEnd::Never => Ok(()),
+			RecurrenceEnd::Count(count) => write!(f, ";COUNT={count}"),
+			RecurrenceEnd::Until(until) => write!(f, ";UNTIL={}", until.format("%Y%m%dT%H%M%S")),
+		}
+	}
+}
+
+/// The error returned when a RRULE can't be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseRuleError(String);
+
+impl fmt::Display for ParseRuleError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "invalid recurrence rule: {}", self.0)
+	}
+}
+
+impl std::error::Error for ParseRuleError {}
+
+impl FromStr for RecurrenceRule {
+	type Err = ParseRuleError;
+
+	fn from_str(text: &str) -> Result<Self, Self::Err> {
+		let text = text.trim();
+		let text = text.strip_prefix("RRULE:").unwrap_or(text);
+		let error = |message: String| ParseRuleError(message);
+
+		let mut frequency = None;
+		let mut rule = RecurrenceRule::default();
+		let mut keys = Vec::new();
+		for part in text.split(';').filter(|part| !part.is_empty()) {
+			let (key, value) = part
+				.split_once('=')
+				.ok_or_else(|| error(format!("expected KEY=VALUE, found `{part}`")))?;
+			let invalid = || error(format!("invalid {key} `{value}`"));
+			let key_upper = key.to_ascii_uppercase();
+			if keys.contains(&key_upper) {
+				return Err(error(format!("duplicate {key_upper}")));
+			}
+			match key_upper.as_str() {
+				"FREQ" => {
+					let found = Frequency::ALL
+						.into_iter()
+						.find(|frequency| frequency.keyword().eq_ignore_ascii_case(value));
+					frequency = Some(found.ok_or_else(invalid)?);
+				}
+				"INTERVAL" => {
+					rule.interval = value.parse().ok().filter(|interval| *interval > 0).ok_or_else(invalid)?;
+				}
+				"BYDAY" => {
+					rule.by_day = value
+						.split(',')
+						.map(|code| parse_weekday(code).ok_or_else(invalid))
+						.collect::<Result<_, _>>()?;
+				}
+				"COUNT" | "UNTIL" if rule.end != RecurrenceEnd::Never => {
+					return Err(error("COUNT and UNTIL can't both be given".to_string()));
+				}
+				"COUNT" => rule.end = RecurrenceEnd::Count(value.parse().map_err(|_| invalid())?),
+				"UNTIL" => rule.end = RecurrenceEnd::Until(parse_until(value).ok_or_else(invalid)?),
+				// weeks always start on monday
+				"WKST" if value.eq_ignore_ascii_case("MO") => {}
+				_ => return Err(error(format!("unsupported part `{part}`"))),
+			}
+			keys.push(key_upper);
+		}
+		rule.frequency = frequency.ok_or_else(|| error("missing FREQ".to_string()))?;
+		Ok(rule)
+	}
+}
+
+const WEEKDAY_CODES: [&str; 7] = ["MO", "TU", "WE", "TH", "FR", "SA", "SU"];
+
+fn weekday_code(weekday: Weekday) -> &'static str {
+	WEEKDAY_CODES[weekday.num_days_from_monday() as usize]
+}
+
+fn parse_weekday(code: &str) -> Option<Weekday> {
+	let index = WEEKDAY_CODES.iter().position(|known| known.eq_ignore_ascii_case(code.trim()))?;
+	Weekday::try_from(index as u8).ok()
+}
+
+/// Parse a date, or a date and time with an optional `Z`, which is ignored.
+/// A date without time ends with that day.
+fn parse_until(value: &str) -> Option<NaiveDateTime> {
+	let value = value.trim_end_matches(['Z', 'z']);
+	NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok().or_else(|| {
+		let day = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+		day.and_hms_opt(23, 59, 59)
+	})
+}
+
+/// Form controls to edit a [`RecurrenceRule`] and the start of the repeating event.
+///
+/// Below them a [`Calendar`] selects the start and highlights the occurrences in the shown
+/// months. Weekdays can be picked for weekly rules, changing the frequency resets them.
+pub struct RecurrenceEditor<'a> {
+	id: Id,
+	rule: &'a mut RecurrenceRule,
+	start: &'a mut NaiveDateTime,
+	locale: Locale,
+	months: u32,
+	preview_color: Option<Color32>,
+}
+
+impl<'a> RecurrenceEditor<'a> {
+	/// Create new editor with unique id and mutable references to the rule and the start of
+	/// the event.
+	pub fn new<T: Hash>(id: T, rule: &'a mut RecurrenceRule, start: &'a mut NaiveDateTime) -> Self {
+		Self {
+			id: Id::new(id),
+			rule,
+			start,
+			locale: Locale::default(),
+			months: 1,
+			preview_color: None,
+		}
+	}
+
+	/// Set the language of month and weekday names and the first day of the week.
+	#[must_use]
+	pub fn locale(mut self, locale: Locale) -> Self {
+		self.locale = locale;
+		self
+	}
+
+	/// Preview this many consecutive months side by side.
+	/// Default is 1
+	#[must_use]
+	pub fn months(mut self, months: u32) -> Self {
+		self.months = months.max(1);
+		self
+	}
+
+	/// Set the background of days with an occurrence.
+	/// Default is a faded selection color
+	#[must_use]
+	pub fn preview_color(mut self, color: Color32) -> Self {
+		self.preview_color = Some(color);
+		self
+	}
+
+	/// Draw the interval and the frequency.
+	fn show_frequency(&mut self, ui: &mut Ui) {
+		ui.horizontal(|ui| {
+			ui.label(self.locale.repeat_every);
+			ui.add(DragValue::new(&mut self.rule.interval).clamp_range(1..=999));
+			let old_frequency = self.rule.frequency;
+			egui::ComboBox::from_id_source(self.id.with("frequency"))
+				.selected_text(self.rule.frequency.unit(&self.locale))
+				.show_ui(ui, |ui| {
+					for frequency in Frequency::ALL {
+						ui.selectable_value(&mut self.rule.frequency, frequency, frequency.unit(&self.locale));
+					}
+				});
+			if self.rule.frequency != old_frequency {
+				self.rule.by_day.clear();
+			}
+		});
+	}
+
+	/// Draw a toggle for each day of the week, in the order of the locale.
+	fn show_weekdays(&mut self, ui: &mut Ui) {
+		ui.horizontal(|ui| {
+			let start_weekday = self.start.weekday();
+			let mut weekday = self.locale.first_weekday;
+			for _ in 0..7 {
+				let is_on = if self.rule.by_day.is_empty() {
+					weekday == start_weekday
+				} else {
+					self.rule.by_day.contains(&weekday)
+				};
+				if ui.selectable_label(is_on, self.locale.weekday_name(weekday)).clicked() {
+					if self.rule.by_day.is_empty() {
+						self.rule.by_day.push(start_weekday);
+					}
+					if is_on {
+						self.rule.by_day.retain(|day| *day != weekday);
+					} else {
+						self.rule.by_day.push(weekday);
+						self.rule.by_day.sort_by_key(Weekday::num_days_from_monday);
+					}
+				}
+				weekday = weekday.succ();
+			}
+		});
+	}
+
+	/// Draw the choice between never ending, a number of occurrences and an end date.
+	fn show_end(&mut self, ui: &mut Ui) {
+		let after_start = *self.start..;
+		let locale = self.locale;
+		let end = &mut self.rule.end;
+		ui.horizontal(|ui| {
+			ui.label(locale.ends);
+			if ui.radio(*end == RecurrenceEnd::Never, locale.ends_never).clicked() {
+				*end = RecurrenceEnd::Never;
+			}
+			if ui.radio(matches!(end, RecurrenceEnd::Count(_)), locale.ends_after).clicked()
+				&& !matches!(end, RecurrenceEnd::Count(_))
+			{
+				*end = RecurrenceEnd::Count(10);
+			}
+			if let RecurrenceEnd::Count(count) = end {
+				ui.add(DragValue::new(count).clamp_range(1..=9999).suffix(locale.times_suffix));
+			}
+			if ui.radio(matches!(end, RecurrenceEnd::Until(_)), locale.ends_on).clicked()
+				&& !matches!(end, RecurrenceEnd::Until(_))
+			{
+				let until = after_start.start.checked_add_months(Months::new(1));
+				*end = RecurrenceEnd::Until(until.unwrap_or(after_start.start));
+			}
+			if let RecurrenceEnd::Until(until) = end {
+				ui.add(
+					DatePicker::<RangeFrom<NaiveDateTime>>::new(self.id.with("until"), until)
+						.locale(self.locale)
+						.restrict_range(&after_start),
+				);
+			}
+		});
+	}
+
+	/// Draw the calendar selecting the start, with the occurrences in the shown months
+	/// highlighted.
+	fn show_preview(&mut self, ui: &mut Ui) {
+		let color = self
+			.preview_color
+			.unwrap_or_else(|| ui.visuals().selection.bg_fill.linear_multiply(0.4));
+		// the shown months always contain the start, plus the days of adjacent months
+		let shown_until = self
+			.start
+			.date()
+			.with_day(1)
+			.and_then(|first| first.checked_add_months(Months::new(self.months)))
+			.and_then(|first_hidden| first_hidden.checked_add_days(Days::new(14)))
+			.unwrap_or(NaiveDate::MAX);
+		let occurrences: BTreeSet<NaiveDate> = self
+			.rule
+			.occurrences_until(*self.start, shown_until)
+			.map(|date| date.date())
+			.collect();
+
+		ui.add(
+			Calendar::<RangeFull>::new(self.id.with("preview"), self.start)
+				.locale(self.locale)
+				.months(self.months)
+				.decorate_days(move |day| {
+					let decoration = DayDecoration::default();
+					if occurrences.contains(&day) {
+						decoration.fill(color)
+					} else {
+						decoration
+					}
+				}),
+		);
+	}
+}
+
+impl Widget for RecurrenceEditor<'_> {
+	fn ui(mut self, ui: &mut Ui) -> Response {
+		let old_rule = self.rule.clone();
+		let old_start = *self.start;
+		let mut response = ui
+			.vertical(|ui| {
+				self.show_frequency(ui);
+				if self.rule.frequency == Frequency::Weekly {
+					self.show_weekdays(ui);
+				}
+				self.show_end(ui);
+				self.show_preview(ui);
+			})
+			.response;
+		if *self.rule != old_rule || *self.start != old_start {
+			response.mark_changed();
+		}
+		response
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+		NaiveDate::from_ymd_opt(year, month, day).unwrap()
+	}
+
+	fn parse(text: &str) -> Result<RecurrenceRule, ParseRuleError> {
+		text.parse()
+	}
+
+	#[test]
+	fn round_trip() {
+		for text in [
+			"FREQ=DAILY",
+			"FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE,FR",
+			"FREQ=MONTHLY;BYDAY=SU;COUNT=5",
+			"FREQ=YEARLY;INTERVAL=3;UNTIL=20301231T120000",
+		] {
+			assert_eq!(parse(text).unwrap().to_string(), text);
+		}
+	}
+
+	#[test]
+	fn parse_by_day() {
+		let rule = parse("RRULE:freq=weekly;byday=tu, th").unwrap();
+		assert_eq!(rule.frequency, Frequency::Weekly);
+		assert_eq!(rule.by_day, [Weekday::Tue, Weekday::Thu]);
+		assert!(parse("FREQ=WEEKLY;BYDAY=MO,XX").is_err());
+		assert!(parse("FREQ=WEEKLY;BYDAY=1MO").is_err());
+	}
+
+	#[test]
+	fn parse_until() {
+		let rule = parse("FREQ=DAILY;UNTIL=20230601").unwrap();
+		assert_eq!(rule.end, RecurrenceEnd::Until(date(2023, 6, 1).and_hms_opt(23, 59, 59).unwrap()));
+		let rule = parse("FREQ=DAILY;UNTIL=20230601T083000Z").unwrap();
+		assert_eq!(rule.end, RecurrenceEnd::Until(date(2023, 6, 1).and_hms_opt(8, 30, 0).unwrap()));
+		assert_eq!(rule.to_string(), "FREQ=DAILY;UNTIL=20230601T083000");
+		assert!(parse("FREQ=DAILY;UNTIL=2023-06-01").is_err());
+	}
+
+	#[test]
+	fn reject_invalid() {
+		assert_eq!(parse("FREQ=DAILY;INTERVAL=0"), Err(ParseRuleError("invalid INTERVAL `0`".to_string())));
+		assert_eq!(
+			parse("FREQ=DAILY;COUNT=2;UNTIL=20230601"),
+			Err(ParseRuleError("COUNT and UNTIL can't both be given".to_string()))
+		);
+		assert_eq!(parse("FREQ=DAILY;COUNT=2;COUNT=3"), Err(ParseRuleError("duplicate COUNT".to_string())));
+		assert_eq!(parse("FREQ=DAILY;FREQ=WEEKLY"), Err(ParseRuleError("duplicate FREQ".to_string())));
+		assert_eq!(
+			parse("FREQ=DAILY;BYMONTH=1"),
+			Err(ParseRuleError("unsupported part `BYMONTH=1`".to_string()))
+		);
+		assert_eq!(parse("INTERVAL=2"), Err(ParseRuleError("missing FREQ".to_string())));
+	}
+
+	#[test]
+	fn never_matching_rule_ends() {
+		// every 7 days from a monday is never a tuesday
+		let rule = parse("FREQ=DAILY;INTERVAL=7;BYDAY=TU").unwrap();
+		let start = date(2023, 5, 1).and_hms_opt(9, 0, 0).unwrap();
+		assert_eq!(rule.occurrences_until(start, date(2023, 8, 1)).count(), 0);
+		assert_eq!(rule.occurrences(start).count(), 0);
+	}
+}
@@ -0,0 +1,189 @@
+//! Picking a layout by the available width, like media queries in CSS.
+//!
+//! ```no_run
+//! # use grus_gui_lib::{Create, Label, WidgetPlacer};
+//! # use grus_gui_lib::responsive::{Breakpoint, Responsive};
+//! # use egui::{Align, Layout};
+//! # fn draw(ui: &mut egui::Ui) {
+//! let mut placer = WidgetPlacer::new(ui);
+//! let ((name, value), _rect) = Responsive::new("details")
+//!     .breakpoint(Breakpoint::new("narrow", 0.0, Layout::top_down(Align::Min)))
+//!     .breakpoint(Breakpoint::new("wide", 400.0, Layout::left_to_right(Align::Center)))
+//!     .show(&mut placer, |placer, breakpoint| {
+//!         let name = placer.create(Label::new("Name"));
+//!         let value = placer.create(Label::new(format!("shown {}", breakpoint.name())));
+//!         (name, value)
+//!     });
+//! # }
+//! ```
+
+use std::collections::BTreeMap;
+use std::hash::Hash;
+
+use egui::{Context, Id, Rect};
+
+use crate::{ExtLayout, Layout, WidgetPlacer};
+
+/// A named [`Layout`] used when at least `min_width` is available.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Breakpoint {
+	name: String,
+	min_width: f32,
+	layout: Layout,
+	columns: Option<usize>,
+}
+
+impl Breakpoint {
+	pub fn new(name: impl Into<String>, min_width: f32, layout: impl Into<Layout>) -> Self {
+		Breakpoint {
+			name: name.into(),
+			min_width,
+			layout: layout.into(),
+			columns: None,
+		}
+	}
+
+	/// Also suggest a number of grid columns, e.g. for [`ExtLayout::columns_balanced`].
+	pub fn grid_columns(mut self, columns: usize) -> Self {
+		self.columns = Some(columns);
+		self
+	}
+
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	pub fn min_width(&self) -> f32 {
+		self.min_width
+	}
+
+	pub fn layout(&self) -> Layout {
+		self.layout
+	}
+
+	pub fn columns(&self) -> Option<usize> {
+		self.columns
+	}
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct State {
+	/// The breakpoint shown last frame.
+	breakpoint: Option<String>,
+	/// The width the contents took with each breakpoint, the last time it was shown.
+	content_widths: BTreeMap<String, f32>,
+}
+
+impl State {
+	pub fn load(ctx: &Context, id: Id) -> Option<Self> {
+		ctx.data_mut(|d| d.get_temp(id))
+	}
+
+	pub fn store(self, ctx: &Context, id: Id) {
+		ctx.data_mut(|d| d.insert_temp(id, self));
+	}
+
+	/// Pick the breakpoint for `available_width`, with breakpoints sorted by their `min_width`.
+	///
+	/// To not jump back and forth between two breakpoints, a wider breakpoint is only picked
+	/// once there is `hysteresis` more width than it needs, a narrower one only when there is
+	/// `hysteresis` less width than the current one needs, and never a breakpoint that did not
+	/// fit the last time it was shown.
+	fn pick(&self, breakpoints: &[Breakpoint], available_width: f32, hysteresis: f32) -> usize {
+		let fitting = breakpoints
+			.iter()
+			.rposition(|breakpoint| breakpoint.min_width <= available_width)
+			.unwrap_or(0);
+		let current = self
+			.breakpoint
+			.as_ref()
+			.and_then(|name| breakpoints.iter().position(|breakpoint| &breakpoint.name == name));
+
+		let mut index = match current {
+			Some(current) if fitting > current => breakpoints[..=fitting]
+				.iter()
+				.rposition(|breakpoint| breakpoint.min_width + hysteresis <= available_width)
+				.map_or(current, |index| index.max(current)),
+			Some(current) if fitting < current => {
+				if available_width < breakpoints[current].min_width - hysteresis {
+					fitting
+				} else {
+					current
+				}
+			}
+			_ => fitting,
+		};
+		while index > 0
+			&& self
+				.content_widths
+				.get(&breakpoints[index].name)
+				.is_some_and(|&width| width > available_width + 0.5)
+		{
+			index -= 1;
+		}
+		index
+	}
+}
+
+/// A container showing its contents with one of several [`Layout`]s, depending on the
+/// width available to it, e.g. in a row on wide screens and stacked on narrow ones.
+///
+/// The chosen breakpoint and the widths of the contents are remembered between frames,
+/// so that the layout does not flip every frame when the contents change the available width.
+#[must_use = "You should call .show()"]
+pub struct Responsive {
+	id: Id,
+	breakpoints: Vec<Breakpoint>,
+	hysteresis: f32,
+}
+
+impl Responsive {
+	/// Create a new [`Responsive`] container with a locally unique identifier.
+	pub fn new(id_source: impl Hash) -> Self {
+		Responsive {
+			id: Id::new(id_source),
+			breakpoints: Vec::new(),
+			hysteresis: 8.0,
+		}
+	}
+
+	/// Add a breakpoint. The widest one whose `min_width` fits is used.
+	pub fn breakpoint(mut self, breakpoint: Breakpoint) -> Self {
+		self.breakpoints.push(breakpoint);
+		self
+	}
+
+	/// How much the available width needs to pass a breakpoint before switching to it.
+	/// Default: 8 points.
+	pub fn hysteresis(mut self, hysteresis: f32) -> Self {
+		self.hysteresis = hysteresis;
+		self
+	}
+
+	/// Lay out `add_contents` with the breakpoint picked for the available width.
+	///
+	/// Without breakpoints the layout of the placer is used.
+	pub fn show<R>(
+		self,
+		placer: &mut WidgetPlacer,
+		add_contents: impl FnOnce(&mut WidgetPlacer, &Breakpoint) -> R,
+	) -> (R, Rect) {
+		let Responsive { id, mut breakpoints, hysteresis } = self;
+		if breakpoints.is_empty() {
+			breakpoints.push(Breakpoint::new("default", 0.0, *placer.placer.layout()));
+		}
+		breakpoints.sort_by(|a, b| a.min_width.total_cmp(&b.min_width));
+
+		let available_width = placer.placer.available_size().x;
+		let mut state = State::load(placer.context, id).unwrap_or_default();
+		let breakpoint = &breakpoints[state.pick(&breakpoints, available_width, hysteresis)];
+
+		let (ret, rect) = placer.with_layout(breakpoint.layout, |placer| add_contents(placer, breakpoint));
+
+		state.breakpoint = Some(breakpoint.name.clone());
+		state.content_widths.insert(breakpoint.name.clone(), rect.width());
+		state.store(placer.context, id);
+
+		(ret, rect)
+	}
+}
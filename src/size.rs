@@ -0,0 +1,153 @@
+//! Size constraints for a child region of a [`WidgetPlacer`].
+//!
+//! ```no_run
+//! # use grus_gui_lib::{Create, Label, WidgetPlacer};
+//! # use grus_gui_lib::size::SizeConstraints;
+//! # fn draw(ui: &mut egui::Ui) {
+//! let mut placer = WidgetPlacer::new(ui);
+//! // A sidebar a quarter of the width, but at least 120 and at most 240 points wide:
+//! let (label, _rect) = SizeConstraints::new()
+//!     .width_fraction(0.25)
+//!     .min_width(120.0)
+//!     .max_width(240.0)
+//!     .show(&mut placer, |placer| placer.create(Label::new("Sidebar")));
+//! // A 16:9 preview as wide as possible:
+//! let (preview, _rect) = SizeConstraints::new()
+//!     .aspect_ratio(16.0 / 9.0)
+//!     .show(&mut placer, |placer| placer.create(Label::new("Preview")));
+//! # }
+//! ```
+
+use egui::{Rect, Vec2};
+
+use crate::{Layout, WidgetPlacer};
+
+/// Size constraints for one child region, applied with [`Self::show`].
+///
+/// Sizes given as a fraction or by an aspect ratio are exact, and clamped to the minimum and
+/// maximum sizes. Otherwise the region is as wide as allowed, and as high as its contents.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[must_use = "You should call .show()"]
+pub struct SizeConstraints {
+	min_size: Vec2,
+	max_size: Vec2,
+	width_fraction: Option<f32>,
+	height_fraction: Option<f32>,
+	aspect_ratio: Option<f32>,
+	layout: Option<Layout>,
+}
+
+impl Default for SizeConstraints {
+	fn default() -> Self {
+		SizeConstraints {
+			min_size: Vec2::ZERO,
+			max_size: Vec2::INFINITY,
+			width_fraction: None,
+			height_fraction: None,
+			aspect_ratio: None,
+			layout: None,
+		}
+	}
+}
+
+impl SizeConstraints {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn min_width(mut self, width: f32) -> Self {
+		self.min_size.x = width;
+		self
+	}
+
+	pub fn max_width(mut self, width: f32) -> Self {
+		self.max_size.x = width;
+		self
+	}
+
+	pub fn min_height(mut self, height: f32) -> Self {
+		self.min_size.y = height;
+		self
+	}
+
+	pub fn max_height(mut self, height: f32) -> Self {
+		self.max_size.y = height;
+		self
+	}
+
+	/// Set both the minimum and maximum width.
+	pub fn exact_width(self, width: f32) -> Self {
+		self.min_width(width).max_width(width)
+	}
+
+	/// Set both the minimum and maximum height.
+	pub fn exact_height(self, height: f32) -> Self {
+		self.min_height(height).max_height(height)
+	}
+
+	/// Take this fraction of the width of the parent placer, e.g. `0.5` for half of it, whatever
+	/// earlier widgets took.
+	pub fn width_fraction(mut self, fraction: f32) -> Self {
+		self.width_fraction = Some(fraction);
+		self
+	}
+
+	/// Take this fraction of the height of the parent placer, e.g. `0.5` for half of it, whatever
+	/// earlier widgets took.
+	pub fn height_fraction(mut self, fraction: f32) -> Self {
+		self.height_fraction = Some(fraction);
+		self
+	}
+
+	/// Keep the width divided by the height at `ratio`, deriving the height from the width.
+	pub fn aspect_ratio(mut self, ratio: f32) -> Self {
+		self.aspect_ratio = Some(ratio);
+		self
+	}
+
+	/// The layout of the child region. Default: the layout of the parent.
+	pub fn layout(mut self, layout: impl Into<Layout>) -> Self {
+		self.layout = Some(layout.into());
+		self
+	}
+
+	/// The minimum and maximum size of the child region, given the size of the parent and the
+	/// size still available in it.
+	fn resolve(&self, parent: Vec2, available: Vec2) -> (Vec2, Vec2) {
+		let (mut min, mut max) = (self.min_size, self.max_size.max(self.min_size));
+		if let Some(fraction) = self.width_fraction {
+			min.x = (fraction * parent.x).clamp(min.x, max.x);
+			max.x = min.x;
+		}
+		if let Some(fraction) = self.height_fraction {
+			min.y = (fraction * parent.y).clamp(min.y, max.y);
+			max.y = min.y;
+		}
+		if let Some(ratio) = self.aspect_ratio.filter(|ratio| *ratio > 0.0) {
+			let width = max.x.min(available.x).max(min.x);
+			let height = (width / ratio).clamp(min.y, max.y);
+			min = Vec2::new(height * ratio, height);
+			max = min;
+		}
+		(min, max)
+	}
+
+	/// Lay out the widgets added by `add_contents` in a child region with these constraints.
+	pub fn show<R>(self, placer: &mut WidgetPlacer, add_contents: impl FnOnce(&mut WidgetPlacer) -> R) -> (R, Rect) {
+		let parent = placer.placer.max_rect().size();
+		let available = placer.placer.available_rect_before_wrap().size();
+		let (min, max) = self.resolve(parent, available);
+		let desired_size = Vec2::new(max.x.min(available.x).max(min.x), min.y);
+		let layout = self.layout.unwrap_or(*placer.placer.layout());
+
+		placer.allocate_ui_with_layout(desired_size, layout, |placer| {
+			if max.y.is_finite() {
+				placer.set_max_height(max.y);
+			}
+			let ret = add_contents(placer);
+			placer.set_min_width(min.x);
+			placer.set_min_height(min.y);
+			ret
+		})
+	}
+}
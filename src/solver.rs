@@ -0,0 +1,524 @@
+//! An implementation of the Cassowary simplex solver, following the design of the kiwi solver.
+//!
+//! Constraints are `expression OP 0` where the expression is linear in the external variables.
+//! Non-required constraints get error variables that are minimized in the objective, weighted by
+//! their strength.
+
+use std::collections::BTreeMap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum SymbolKind {
+	Invalid,
+	External,
+	Slack,
+	Error,
+	Dummy,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Symbol {
+	id: usize,
+	kind: SymbolKind,
+}
+
+impl Symbol {
+	const INVALID: Symbol = Symbol { id: 0, kind: SymbolKind::Invalid };
+
+	fn is_pivotable(self) -> bool {
+		matches!(self.kind, SymbolKind::Slack | SymbolKind::Error)
+	}
+}
+
+fn near_zero(value: f64) -> bool {
+	value.abs() < 1.0e-8
+}
+
+/// A linear combination of symbols plus a constant.
+#[derive(Clone, Debug, Default)]
+struct Row {
+	constant: f64,
+	cells: BTreeMap<Symbol, f64>,
+}
+
+impl Row {
+	fn new(constant: f64) -> Self {
+		Row { constant, cells: BTreeMap::new() }
+	}
+
+	fn insert_symbol(&mut self, symbol: Symbol, coefficient: f64) {
+		let cell = self.cells.entry(symbol).or_insert(0.0);
+		*cell += coefficient;
+		if near_zero(*cell) {
+			self.cells.remove(&symbol);
+		}
+	}
+
+	fn insert_row(&mut self, other: &Row, coefficient: f64) {
+		self.constant += other.constant * coefficient;
+		for (&symbol, &value) in &other.cells {
+			self.insert_symbol(symbol, value * coefficient);
+		}
+	}
+
+	fn remove(&mut self, symbol: Symbol) {
+		self.cells.remove(&symbol);
+	}
+
+	fn reverse_sign(&mut self) {
+		self.constant = -self.constant;
+		for value in self.cells.values_mut() {
+			*value = -*value;
+		}
+	}
+
+	/// Solves `0 = row` for `symbol`, leaving `symbol = row`.
+	fn solve_for(&mut self, symbol: Symbol) {
+		let coefficient = -1.0 / self.cells.remove(&symbol).unwrap_or(1.0);
+		self.constant *= coefficient;
+		for value in self.cells.values_mut() {
+			*value *= coefficient;
+		}
+	}
+
+	/// Solves `lhs = row` for `rhs`, leaving `rhs = row`.
+	fn solve_for_pair(&mut self, lhs: Symbol, rhs: Symbol) {
+		self.insert_symbol(lhs, -1.0);
+		self.solve_for(rhs);
+	}
+
+	fn coefficient_for(&self, symbol: Symbol) -> f64 {
+		self.cells.get(&symbol).copied().unwrap_or(0.0)
+	}
+
+	/// Replaces `symbol` by `row`.
+	fn substitute(&mut self, symbol: Symbol, row: &Row) {
+		if let Some(coefficient) = self.cells.remove(&symbol) {
+			self.insert_row(row, coefficient);
+		}
+	}
+
+	/// Adds `value` to the constant, returning the new constant.
+	fn add(&mut self, value: f64) -> f64 {
+		self.constant += value;
+		self.constant
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Relation {
+	LessOrEqual,
+	Equal,
+	GreaterOrEqual,
+}
+
+/// The required constraint could not be satisfied together with the ones added before.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Unsatisfiable;
+
+/// An external variable that can be moved with [`Solver::suggest_value`].
+#[derive(Clone, Copy, Debug)]
+struct Edit {
+	/// The error symbols of its `variable == constant` constraint.
+	plus: Symbol,
+	minus: Symbol,
+	constant: f64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Solver {
+	rows: BTreeMap<Symbol, Row>,
+	vars: BTreeMap<usize, Symbol>,
+	edits: BTreeMap<usize, Edit>,
+	objective: Row,
+	artificial: Option<Row>,
+	/// Rows with a negative constant after a suggested value, for the dual simplex method.
+	infeasible: Vec<Symbol>,
+	id_tick: usize,
+}
+
+impl Solver {
+	pub(crate) const REQUIRED: f64 = 1_001_001_000.0;
+
+	/// Adds `terms + constant OP 0` with the given strength.
+	///
+	/// A failing constraint is not added, and leaves the solution as it was.
+	pub(crate) fn add_constraint(
+		&mut self,
+		terms: &[(usize, f64)],
+		constant: f64,
+		relation: Relation,
+		strength: f64,
+	) -> Result<(), Unsatisfiable> {
+		self.add_constraint_with_tag(terms, constant, relation, strength).map(|_| ())
+	}
+
+	/// Lets `variable` be moved with [`Self::suggest_value`], as hard as `strength`, which is
+	/// kept below [`Self::REQUIRED`]. Does nothing if it already is an edit variable.
+	pub(crate) fn add_edit_variable(&mut self, variable: usize, strength: f64) {
+		if self.edits.contains_key(&variable) {
+			return;
+		}
+		let strength = strength.clamp(0.0, Self::REQUIRED - 1.0);
+		let (plus, minus) = self
+			.add_constraint_with_tag(&[(variable, 1.0)], 0.0, Relation::Equal, strength)
+			.expect("non-required constraints are always satisfiable");
+		self.edits.insert(variable, Edit { plus, minus, constant: 0.0 });
+	}
+
+	/// Suggests a value for an edit variable, and solves with the dual simplex method.
+	/// Does nothing for other variables.
+	pub(crate) fn suggest_value(&mut self, variable: usize, value: f64) {
+		let Some(edit) = self.edits.get_mut(&variable) else {
+			return;
+		};
+		let delta = value - edit.constant;
+		edit.constant = value;
+		let Edit { plus, minus, .. } = *edit;
+
+		if let Some(row) = self.rows.get_mut(&plus) {
+			if row.add(-delta) < 0.0 {
+				self.infeasible.push(plus);
+			}
+		} else if let Some(row) = self.rows.get_mut(&minus) {
+			if row.add(delta) < 0.0 {
+				self.infeasible.push(minus);
+			}
+		} else {
+			for (&symbol, row) in &mut self.rows {
+				let coefficient = row.coefficient_for(plus);
+				if coefficient != 0.0 && row.add(delta * coefficient) < 0.0 && symbol.kind != SymbolKind::External {
+					self.infeasible.push(symbol);
+				}
+			}
+		}
+		self.dual_optimize();
+	}
+
+	/// The current value of an external variable.
+	pub(crate) fn value(&self, variable: usize) -> f64 {
+		self.vars
+			.get(&variable)
+			.and_then(|symbol| self.rows.get(symbol))
+			.map_or(0.0, |row| row.constant)
+	}
+
+	fn new_symbol(&mut self, kind: SymbolKind) -> Symbol {
+		self.id_tick += 1;
+		Symbol { id: self.id_tick, kind }
+	}
+
+	fn var_symbol(&mut self, variable: usize) -> Symbol {
+		if let Some(&symbol) = self.vars.get(&variable) {
+			return symbol;
+		}
+		let symbol = self.new_symbol(SymbolKind::External);
+		self.vars.insert(variable, symbol);
+		symbol
+	}
+
+	/// Adds a constraint, returning its marker and other symbol, like the error symbols of a
+	/// non-required equality.
+	///
+	/// On failure the new row is dropped again. The pivots done up to then leave an equivalent
+	/// tableau, which only needs to be optimized again.
+	fn add_constraint_with_tag(
+		&mut self,
+		terms: &[(usize, f64)],
+		constant: f64,
+		relation: Relation,
+		strength: f64,
+	) -> Result<(Symbol, Symbol), Unsatisfiable> {
+		let required = strength >= Self::REQUIRED;
+		let mut row = Row::new(constant);
+		for &(variable, coefficient) in terms {
+			if near_zero(coefficient) {
+				continue;
+			}
+			let symbol = self.var_symbol(variable);
+			if let Some(other) = self.rows.get(&symbol) {
+				row.insert_row(other, coefficient);
+			} else {
+				row.insert_symbol(symbol, coefficient);
+			}
+		}
+
+		let (marker, other) = match relation {
+			Relation::LessOrEqual | Relation::GreaterOrEqual => {
+				let coefficient = if relation == Relation::LessOrEqual { 1.0 } else { -1.0 };
+				let slack = self.new_symbol(SymbolKind::Slack);
+				row.insert_symbol(slack, coefficient);
+				if required {
+					(slack, Symbol::INVALID)
+				} else {
+					let error = self.new_symbol(SymbolKind::Error);
+					row.insert_symbol(error, -coefficient);
+					self.objective.insert_symbol(error, strength);
+					(slack, error)
+				}
+			}
+			Relation::Equal if required => {
+				let dummy = self.new_symbol(SymbolKind::Dummy);
+				row.insert_symbol(dummy, 1.0);
+				(dummy, Symbol::INVALID)
+			}
+			Relation::Equal => {
+				let plus = self.new_symbol(SymbolKind::Error);
+				let minus = self.new_symbol(SymbolKind::Error);
+				row.insert_symbol(plus, -1.0);
+				row.insert_symbol(minus, 1.0);
+				self.objective.insert_symbol(plus, strength);
+				self.objective.insert_symbol(minus, strength);
+				(plus, minus)
+			}
+		};
+		if row.constant < 0.0 {
+			row.reverse_sign();
+		}
+
+		let mut subject = Self::choose_subject(&row, marker, other);
+		if subject.kind == SymbolKind::Invalid && row.cells.keys().all(|symbol| symbol.kind == SymbolKind::Dummy) {
+			if !near_zero(row.constant) {
+				return Err(Unsatisfiable);
+			}
+			subject = marker;
+		}
+
+		if subject.kind == SymbolKind::Invalid {
+			if !self.add_with_artificial_variable(row) {
+				// the pivots looking for a solution may have left the objective unoptimized
+				self.optimize(false)?;
+				return Err(Unsatisfiable);
+			}
+		} else {
+			row.solve_for(subject);
+			self.substitute(subject, &row);
+			self.rows.insert(subject, row);
+		}
+
+		self.optimize(false)?;
+		Ok((marker, other))
+	}
+
+	fn choose_subject(row: &Row, marker: Symbol, other: Symbol) -> Symbol {
+		if let Some(&symbol) = row.cells.keys().find(|symbol| symbol.kind == SymbolKind::External) {
+			return symbol;
+		}
+		for symbol in [marker, other] {
+			if symbol.is_pivotable() && row.coefficient_for(symbol) < 0.0 {
+				return symbol;
+			}
+		}
+		Symbol::INVALID
+	}
+
+	/// Adds `row` by minimizing an artificial variable equal to it. Returns whether it could
+	/// reach zero, otherwise the row is dropped again.
+	fn add_with_artificial_variable(&mut self, row: Row) -> bool {
+		let art = self.new_symbol(SymbolKind::Slack);
+		self.rows.insert(art, row.clone());
+		self.artificial = Some(row);
+
+		let optimized = self.optimize(true).is_ok();
+		let success = self.artificial.take().is_some_and(|row| optimized && near_zero(row.constant));
+
+		// A basic symbol does not appear in the other rows, so removing its row is enough.
+		if let Some(mut row) = self.rows.remove(&art) {
+			if row.cells.is_empty() || !success {
+				return success;
+			}
+			let Some(&entering) = row.cells.keys().find(|symbol| symbol.is_pivotable()) else {
+				return false;
+			};
+			row.solve_for_pair(art, entering);
+			self.substitute(entering, &row);
+			self.rows.insert(entering, row);
+		}
+
+		for row in self.rows.values_mut() {
+			row.remove(art);
+		}
+		self.objective.remove(art);
+		success
+	}
+
+	fn substitute(&mut self, symbol: Symbol, row: &Row) {
+		for (&basic, other) in &mut self.rows {
+			other.substitute(symbol, row);
+			if basic.kind != SymbolKind::External && other.constant < 0.0 {
+				self.infeasible.push(basic);
+			}
+		}
+		self.objective.substitute(symbol, row);
+		if let Some(artificial) = &mut self.artificial {
+			artificial.substitute(symbol, row);
+		}
+	}
+
+	/// Runs the simplex method on the objective, or the artificial objective.
+	fn optimize(&mut self, artificial: bool) -> Result<(), Unsatisfiable> {
+		loop {
+			let objective = if artificial {
+				self.artificial.as_ref().unwrap_or(&self.objective)
+			} else {
+				&self.objective
+			};
+			let entering = objective
+				.cells
+				.iter()
+				.find(|(symbol, &value)| symbol.kind != SymbolKind::Dummy && value < 0.0)
+				.map(|(&symbol, _)| symbol);
+			let Some(entering) = entering else {
+				return Ok(());
+			};
+
+			let mut ratio = f64::MAX;
+			let mut leaving = None;
+			for (&symbol, row) in &self.rows {
+				if symbol.kind == SymbolKind::External {
+					continue;
+				}
+				let coefficient = row.coefficient_for(entering);
+				if coefficient < 0.0 {
+					let r = -row.constant / coefficient;
+					if r < ratio {
+						ratio = r;
+						leaving = Some(symbol);
+					}
+				}
+			}
+			// An unbounded objective means a constraint is degenerate.
+			let leaving = leaving.ok_or(Unsatisfiable)?;
+
+			let mut row = self.rows.remove(&leaving).unwrap_or_default();
+			row.solve_for_pair(leaving, entering);
+			self.substitute(entering, &row);
+			self.rows.insert(entering, row);
+		}
+	}
+
+	/// Runs the dual simplex method on the rows made infeasible by a suggested value.
+	fn dual_optimize(&mut self) {
+		while let Some(leaving) = self.infeasible.pop() {
+			let Some(row) = self.rows.get(&leaving).filter(|row| row.constant < 0.0) else {
+				continue;
+			};
+			let mut ratio = f64::MAX;
+			let mut entering = None;
+			for (&symbol, &coefficient) in &row.cells {
+				if coefficient > 0.0 && symbol.kind != SymbolKind::Dummy {
+					let r = self.objective.coefficient_for(symbol) / coefficient;
+					if r < ratio {
+						ratio = r;
+						entering = Some(symbol);
+					}
+				}
+			}
+			// Only happens for an inconsistent tableau, which the primal method never leaves.
+			let Some(entering) = entering else {
+				continue;
+			};
+
+			let mut row = self.rows.remove(&leaving).unwrap_or_default();
+			row.solve_for_pair(leaving, entering);
+			self.substitute(entering, &row);
+			self.rows.insert(entering, row);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const STRONG: f64 = 1_000_000.0;
+	const WEAK: f64 = 1.0;
+
+	fn assert_value(solver: &Solver, variable: usize, expected: f64) {
+		let value = solver.value(variable);
+		assert!((value - expected).abs() < 1e-6, "variable {variable} is {value}, expected {expected}");
+	}
+
+	/// `variable == value`
+	fn fix(solver: &mut Solver, variable: usize, value: f64, strength: f64) -> Result<(), Unsatisfiable> {
+		solver.add_constraint(&[(variable, 1.0)], -value, Relation::Equal, strength)
+	}
+
+	#[test]
+	fn required_constraints() {
+		let mut solver = Solver::default();
+		fix(&mut solver, 0, 10.0, Solver::REQUIRED).unwrap();
+		// y == x + 5
+		solver.add_constraint(&[(1, 1.0), (0, -1.0)], -5.0, Relation::Equal, Solver::REQUIRED).unwrap();
+		// z >= y + 10
+		solver.add_constraint(&[(2, 1.0), (1, -1.0)], -10.0, Relation::GreaterOrEqual, Solver::REQUIRED).unwrap();
+		fix(&mut solver, 2, 0.0, WEAK).unwrap();
+		assert_value(&solver, 0, 10.0);
+		assert_value(&solver, 1, 15.0);
+		assert_value(&solver, 2, 25.0);
+	}
+
+	#[test]
+	fn stronger_constraints_win() {
+		let mut solver = Solver::default();
+		fix(&mut solver, 0, 10.0, WEAK).unwrap();
+		assert_value(&solver, 0, 10.0);
+		fix(&mut solver, 0, 20.0, STRONG).unwrap();
+		assert_value(&solver, 0, 20.0);
+		// any number of weak constraints lose against a strong one
+		for _ in 0..10 {
+			fix(&mut solver, 0, 30.0, WEAK).unwrap();
+		}
+		assert_value(&solver, 0, 20.0);
+		solver.add_constraint(&[(0, 1.0)], -40.0, Relation::GreaterOrEqual, Solver::REQUIRED).unwrap();
+		assert_value(&solver, 0, 40.0);
+	}
+
+	#[test]
+	fn unsatisfiable_constraints_are_rolled_back() {
+		let mut solver = Solver::default();
+		fix(&mut solver, 0, 10.0, Solver::REQUIRED).unwrap();
+		// y <= x
+		solver.add_constraint(&[(1, 1.0), (0, -1.0)], 0.0, Relation::LessOrEqual, Solver::REQUIRED).unwrap();
+		fix(&mut solver, 1, 8.0, STRONG).unwrap();
+
+		assert!(fix(&mut solver, 0, 20.0, Solver::REQUIRED).is_err());
+		// y >= x + 1 conflicts with y <= x
+		let conflict = solver.add_constraint(&[(1, 1.0), (0, -1.0)], -1.0, Relation::GreaterOrEqual, Solver::REQUIRED);
+		assert!(conflict.is_err());
+		assert_value(&solver, 0, 10.0);
+		assert_value(&solver, 1, 8.0);
+
+		// the solver keeps working
+		fix(&mut solver, 1, 12.0, STRONG * 10.0).unwrap();
+		assert_value(&solver, 1, 10.0);
+		fix(&mut solver, 2, 3.0, Solver::REQUIRED).unwrap();
+		assert_value(&solver, 2, 3.0);
+	}
+
+	#[test]
+	fn suggest_edit_variables() {
+		let mut solver = Solver::default();
+		// x == y + 5, x >= 0
+		solver.add_constraint(&[(0, 1.0), (1, -1.0)], -5.0, Relation::Equal, Solver::REQUIRED).unwrap();
+		solver.add_constraint(&[(0, 1.0)], 0.0, Relation::GreaterOrEqual, Solver::REQUIRED).unwrap();
+		fix(&mut solver, 1, 0.0, WEAK).unwrap();
+		solver.add_edit_variable(1, STRONG);
+
+		solver.suggest_value(1, 10.0);
+		assert_value(&solver, 0, 15.0);
+		assert_value(&solver, 1, 10.0);
+		solver.suggest_value(1, -3.0);
+		assert_value(&solver, 0, 2.0);
+		assert_value(&solver, 1, -3.0);
+		// x >= 0 is required, so y stops at -5
+		solver.suggest_value(1, -10.0);
+		assert_value(&solver, 0, 0.0);
+		assert_value(&solver, 1, -5.0);
+		solver.suggest_value(1, 20.0);
+		assert_value(&solver, 0, 25.0);
+
+		// suggesting a variable that is not edited does nothing
+		solver.suggest_value(0, 100.0);
+		assert_value(&solver, 0, 25.0);
+	}
+}
@@ -0,0 +1,186 @@
+//! Layering widgets on top of each other in the same rect.
+//!
+//! ```no_run
+//! # use grus_gui_lib::{Button, Create, Label, Paint, WidgetPlacer};
+//! # use egui::Align2;
+//! # fn draw(ui: &mut egui::Ui) {
+//! let mut placer = WidgetPlacer::new(ui);
+//! let mut inbox = placer.z_stack(|stack| {
+//!     stack.layer(Align2::CENTER_CENTER, |placer| placer.create(Button::new("📥 Inbox")));
+//!     stack.layer(Align2::RIGHT_TOP, |placer| placer.create(Label::new("3")));
+//! });
+//! let response = inbox.interact(ui);
+//! ui.paint(&inbox, &response);
+//! if inbox.response(0).is_some_and(|response| response.clicked()) {
+//!     // open the inbox
+//! }
+//! # }
+//! ```
+
+use core::ops::RangeBounds;
+
+use chrono::NaiveDateTime;
+use egui::{Align, Align2, Rect, Response, Sense, Ui, Vec2};
+
+use crate::datepicker::LaidOutDatePicker;
+use crate::placer::Placer;
+use crate::timegrid::LaidOutTimeGrid;
+use crate::timeline::LaidOutTimeline;
+use crate::{
+	LaidOutButton, LaidOutCheckbox, LaidOutFrame, LaidOutLabel, LaidOutRadioButton, Layout, Paint, Placed, WidgetPlacer,
+};
+
+/// A laid out widget that can be a layer of a [`LaidOutZStack`].
+pub trait Layer: Placed {
+	fn interact(&mut self, ui: &mut Ui) -> Response;
+	fn paint(&self, ui: &mut Ui, response: &Response);
+}
+
+macro_rules! impl_layer {
+	($($ty:ty),*) => {$(
+		impl Layer for $ty {
+			fn interact(&mut self, ui: &mut Ui) -> Response {
+				<$ty>::interact(self, ui)
+			}
+
+			fn paint(&self, ui: &mut Ui, response: &Response) {
+				ui.paint(self, response);
+			}
+		}
+	)*};
+}
+
+impl_layer!(
+	LaidOutLabel,
+	LaidOutButton,
+	LaidOutCheckbox,
+	LaidOutRadioButton,
+	LaidOutFrame,
+	LaidOutTimeGrid<'_>,
+	LaidOutTimeline<'_, '_>,
+	LaidOutZStack<'_>
+);
+
+impl<R> Layer for LaidOutDatePicker<'_, '_, R>
+where
+	R: RangeBounds<NaiveDateTime>,
+{
+	fn interact(&mut self, ui: &mut Ui) -> Response {
+		LaidOutDatePicker::interact(self, ui)
+	}
+
+	fn paint(&self, ui: &mut Ui, response: &Response) {
+		ui.paint(self, response);
+	}
+}
+
+/// The layers of a [`WidgetPlacer::z_stack`], all placed in the same frame.
+pub struct ZStack<'p, 'ui, 'a> {
+	parent: &'p WidgetPlacer<'ui>,
+	max_rect: Rect,
+	layers: Vec<(Align2, Box<dyn Layer + 'a>)>,
+}
+
+impl<'a> ZStack<'_, '_, 'a> {
+	/// Add the widget created by `add_contents` on top of the previous layers, placed by `align`
+	/// within the frame shared by all layers. Returns the index of its response.
+	pub fn layer<W: Layer + 'a>(&mut self, align: Align2, add_contents: impl FnOnce(&mut WidgetPlacer) -> W) -> usize {
+		let mut child_wp = WidgetPlacer {
+			placer: Placer::new(self.max_rect, Layout::top_down(Align::Min)),
+			style: self.parent.style,
+			context: self.parent.context,
+			layer_id: self.parent.layer_id,
+		};
+		let widget = add_contents(&mut child_wp);
+		self.layers.push((align, Box::new(widget)));
+		self.layers.len() - 1
+	}
+}
+
+impl<'ui> WidgetPlacer<'ui> {
+	/// Layer the widgets added with [`ZStack::layer`] on top of each other.
+	///
+	/// The frame of the layers is as large as the largest of them. They paint in the order they
+	/// were added, and interact in reverse order, so the top-most layer gets the clicks.
+	#[track_caller]
+	pub fn z_stack<'a>(&mut self, add_layers: impl FnOnce(&mut ZStack<'_, 'ui, 'a>)) -> LaidOutZStack<'a> {
+		let mut stack = ZStack {
+			parent: self,
+			max_rect: self.placer.available_rect_before_wrap(),
+			layers: Vec::new(),
+		};
+		add_layers(&mut stack);
+		let ZStack { layers, .. } = stack;
+
+		let size = layers
+			.iter()
+			.fold(Vec2::ZERO, |size, (_, layer)| size.max(layer.placed_rect().size()));
+		let frame = self.allocate_space(size);
+
+		let mut rect = Rect::from_min_size(frame.min, Vec2::ZERO);
+		let layers = layers
+			.into_iter()
+			.map(|(align, mut layer)| {
+				let layer_rect = align.align_size_within_rect(layer.placed_rect().size(), frame);
+				layer.place(layer_rect);
+				rect = rect.union(layer_rect);
+				layer
+			})
+			.collect();
+
+		LaidOutZStack { rect, layers, responses: Vec::new() }
+	}
+}
+
+/// Widgets layered with [`WidgetPlacer::z_stack`].
+pub struct LaidOutZStack<'a> {
+	rect: Rect,
+	layers: Vec<Box<dyn Layer + 'a>>,
+	responses: Vec<Response>,
+}
+
+impl LaidOutZStack<'_> {
+	pub fn reposition(&mut self, y: f32) {
+		self.place(self.rect.translate(Vec2::new(0., y - self.rect.top())));
+	}
+
+	/// Interact with the layers, top-most first, and then with the whole stack.
+	pub fn interact(&mut self, ui: &mut Ui) -> Response {
+		let mut responses: Vec<Response> = self.layers.iter_mut().rev().map(|layer| layer.interact(ui)).collect();
+		responses.reverse();
+		self.responses = responses;
+
+		let response = ui.interact(self.rect, ui.next_auto_id(), Sense::hover());
+		ui.skip_ahead_auto_ids(1);
+		response
+	}
+
+	/// The response of the layer with the index returned by [`ZStack::layer`],
+	/// once [`Self::interact`] was called.
+	pub fn response(&self, index: usize) -> Option<&Response> {
+		self.responses.get(index)
+	}
+}
+
+impl Placed for LaidOutZStack<'_> {
+	fn placed_rect(&self) -> Rect {
+		self.rect
+	}
+
+	fn place(&mut self, rect: Rect) {
+		let diff = rect.min - self.rect.min;
+		for layer in &mut self.layers {
+			let layer_rect = layer.placed_rect().translate(diff);
+			layer.place(layer_rect);
+		}
+		self.rect = self.rect.translate(diff);
+	}
+}
+
+impl Paint<LaidOutZStack<'_>> for Ui {
+	fn paint(&mut self, lozstack: &LaidOutZStack<'_>, _response: &Response) {
+		for (layer, response) in lozstack.layers.iter().zip(&lozstack.responses) {
+			layer.paint(self, response);
+		}
+	}
+}
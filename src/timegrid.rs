@@ -0,0 +1,493 @@
+//! A week or day agenda with a column per day and a row per hour, showing events as blocks
+//! which can be dragged to move them and resized by their bottom edge.
+//!
+//! Like the other widgets of this crate it is laid out with a [`WidgetPlacer`], then interacted
+//! with and painted.
+//!
+//! ```no_run
+//! use chrono::prelude::*;
+//! use eframe::egui::Ui;
+//! use grus_gui_lib::timegrid::{TimeGrid, TimeGridEvent};
+//! use grus_gui_lib::{Create, Paint, WidgetPlacer};
+//!
+//! fn draw_week(ui: &mut Ui, week: NaiveDate, events: &mut [TimeGridEvent]) {
+//!     let mut placer = WidgetPlacer::new(ui);
+//!     let mut grid = placer.create(TimeGrid::new("team_week", week, events).hours(8, 18));
+//!     let response = grid.interact(ui);
+//!     ui.paint(&grid, &response);
+//! }
+//! ```
+
+use core::ops::Range;
+use std::hash::Hash;
+
+use chrono::naive::NaiveDateTime;
+use chrono::{prelude::*, Duration};
+use eframe::egui::{
+	pos2, style::TextStyle, Align2, Color32, CursorIcon, Id, Rect, Response, Rgba, Sense, Stroke, Ui, Vec2,
+	WidgetText,
+};
+
+use crate::calendar::{days_since_week_start, Locale};
+use crate::{Create, Paint, Placed, WidgetPlacer};
+
+/// An event shown in a [`TimeGrid`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimeGridEvent {
+	pub title: String,
+	pub start: NaiveDateTime,
+	pub end: NaiveDateTime,
+	pub color: Color32,
+	/// The event can't be moved or resized.
+	pub locked: bool,
+}
+
+impl TimeGridEvent {
+	pub fn new(title: impl ToString, start: NaiveDateTime, end: NaiveDateTime) -> Self {
+		Self {
+			title: title.to_string(),
+			start,
+			end,
+			color: Color32::LIGHT_BLUE,
+			locked: false,
+		}
+	}
+
+	pub fn color(mut self, color: Color32) -> Self {
+		self.color = color;
+		self
+	}
+
+	pub fn locked(mut self, locked: bool) -> Self {
+		self.locked = locked;
+		self
+	}
+}
+
+/// Default values of fields are:
+/// - days: `7`, the week containing the given date
+/// - hours: the whole day
+/// - snap_minutes: `15`
+/// - locale: [`Locale::EN`]
+/// - first_weekday: taken from the locale
+/// - weekend_func: `date.weekday() == Weekday::Sat || date.weekday() == Weekday::Sun`
+#[must_use = "You should lay out this widget with a `WidgetPlacer`"]
+pub struct TimeGrid<'a> {
+	id: Id,
+	date: NaiveDate,
+	events: &'a mut [TimeGridEvent],
+	days: u32,
+	hours: Range<u32>,
+	hour_height: Option<f32>,
+	snap_minutes: u32,
+	locale: Locale,
+	// when set, overrides the locale's first day of the week.
+	first_weekday: Option<Weekday>,
+	weekend_color: Color32,
+	weekend_func: fn(&NaiveDateTime) -> bool,
+	highlight_weekend: bool,
+}
+
+impl<'a> TimeGrid<'a> {
+	/// Create new time grid with unique id, a date in the week to show and mutable reference
+	/// to the events.
+	pub fn new<T: Hash>(id: T, date: NaiveDate, events: &'a mut [TimeGridEvent]) -> Self {
+		Self {
+			id: Id::new(id),
+			date,
+			events,
+			days: 7,
+			hours: 0..24,
+			hour_height: None,
+			snap_minutes: 15,
+			locale: Locale::default(),
+			first_weekday: None,
+			weekend_color: Color32::from_rgb(196, 0, 0),
+			weekend_func: |date| date.weekday() == Weekday::Sat || date.weekday() == Weekday::Sun,
+			highlight_weekend: true,
+		}
+	}
+
+	/// Show this many days starting with the date given to [`Self::new`], e.g. `1` for a day
+	/// agenda. Only `7` starts with the first day of the week instead.
+	/// Default is 7
+	pub fn days(mut self, days: u32) -> Self {
+		self.days = days.max(1);
+		self
+	}
+
+	/// Only show the hours from `start` up to `end`, e.g. `hours(8, 18)` for 08:00 to 18:00.
+	/// Default is the whole day
+	pub fn hours(mut self, start: u32, end: u32) -> Self {
+		let end = end.clamp(1, 24);
+		self.hours = start.min(end - 1)..end;
+		self
+	}
+
+	/// Set the height of an hour.
+	/// Default is three times the height of a button
+	pub fn hour_height(mut self, height: f32) -> Self {
+		self.hour_height = Some(height);
+		self
+	}
+
+	/// Moved and resized events snap to multiples of this many minutes.
+	/// Default is 15
+	pub fn snap_minutes(mut self, minutes: u32) -> Self {
+		self.snap_minutes = minutes.clamp(1, 24 * 60);
+		self
+	}
+
+	/// Set the language of weekday names and the first day of the week.
+	pub fn locale(mut self, locale: Locale) -> Self {
+		self.locale = locale;
+		self
+	}
+
+	/// If flag is set to true then the week will start with sunday otherwise monday.
+	/// Default is taken from the locale
+	pub fn sunday_first(self, flag: bool) -> Self {
+		self.first_weekday(if flag { Weekday::Sun } else { Weekday::Mon })
+	}
+
+	/// Set the first day of the week, overriding the one of the locale.
+	pub fn first_weekday(mut self, weekday: Weekday) -> Self {
+		self.first_weekday = Some(weekday);
+		self
+	}
+
+	///If highlight is true then the names of weekend days will be `weekend_color` instead
+	///default text color.
+	pub fn highlight_weekend(mut self, highlight: bool) -> Self {
+		self.highlight_weekend = highlight;
+		self
+	}
+
+	///Set weekends highlighting color.
+	pub fn highlight_weekend_color(mut self, color: Color32) -> Self {
+		self.weekend_color = color;
+		self
+	}
+
+	/// Set function, which will decide if date is a weekend day or not.
+	pub fn weekend_days(mut self, is_weekend: fn(&NaiveDateTime) -> bool) -> Self {
+		self.weekend_func = is_weekend;
+		self
+	}
+
+	fn first_day(&self) -> NaiveDate {
+		if self.days != 7 {
+			return self.date;
+		}
+		let week_start = self.first_weekday.unwrap_or(self.locale.first_weekday);
+		self.date - Duration::days(days_since_week_start(self.date.weekday(), week_start).into())
+	}
+}
+
+impl<'a> Create<TimeGrid<'a>> for WidgetPlacer<'_> {
+	type LaidOutWidget = LaidOutTimeGrid<'a>;
+	#[track_caller]
+	fn create(&mut self, grid: TimeGrid<'a>) -> LaidOutTimeGrid<'a> {
+		let interact_height = self.style.spacing.interact_size.y;
+		let hour_height = grid.hour_height.unwrap_or(3.0 * interact_height);
+		let gutter_width = self
+			.into_galley(WidgetText::from("00:00"), Some(false), f32::INFINITY, TextStyle::Small)
+			.size()
+			.x + 2.0 * self.style.spacing.item_spacing.x;
+
+		let mut width = self.placer.available_size().x;
+		if !width.is_finite() {
+			width = gutter_width + grid.days as f32 * 100.0;
+		}
+		let height = interact_height + grid.hours.len() as f32 * hour_height;
+		let rect = self.allocate_space(Vec2::new(width, height));
+
+		LaidOutTimeGrid {
+			first_day: grid.first_day(),
+			grid,
+			rect,
+			gutter_width,
+			header_height: interact_height,
+			hour_height,
+			event_responses: Vec::new(),
+			dragged_event: None,
+		}
+	}
+}
+
+/// A part of an event inside a single day column.
+struct EventSegment {
+	index: usize,
+	day: u32,
+	rect: Rect,
+	// the last shown segment of the event can be resized, also when the event ends later.
+	is_last: bool,
+}
+
+/// An event being dragged, with its times when the drag started.
+#[derive(Clone, Copy, Debug)]
+struct EventDrag {
+	index: usize,
+	resize: bool,
+	start: NaiveDateTime,
+	end: NaiveDateTime,
+	delta: Vec2,
+}
+
+pub struct LaidOutTimeGrid<'a> {
+	grid: TimeGrid<'a>,
+	first_day: NaiveDate,
+	rect: Rect,
+	gutter_width: f32,
+	header_height: f32,
+	hour_height: f32,
+	// set by `interact`, the rectangles of the event segments and how they were interacted with.
+	event_responses: Vec<(usize, Rect, Response)>,
+	// set by `interact`, the event being dragged.
+	dragged_event: Option<usize>,
+}
+
+impl LaidOutTimeGrid<'_> {
+	pub fn reposition(&mut self, y: f32) {
+		self.rect = self.rect.translate(Vec2::new(0., y - self.rect.top()));
+	}
+
+	/// Interact with the grid and its events, moving and resizing the dragged event.
+	/// The response is marked as changed while an event changes.
+	pub fn interact(&mut self, ui: &mut Ui) -> Response {
+		let mut response = ui.interact(self.rect, ui.next_auto_id(), Sense::hover());
+		ui.skip_ahead_auto_ids(1);
+
+		let drag_id = self.grid.id.with("drag");
+		let mut drag = ui.data(|d| d.get_temp::<EventDrag>(drag_id));
+		self.event_responses.clear();
+		for segment in self.event_segments() {
+			let EventSegment { index, day, rect, is_last } = segment;
+			let event_id = self.grid.id.with(("event", index, day));
+			if self.grid.events[index].locked {
+				let event_response = ui.interact(rect, event_id, Sense::hover());
+				self.event_responses.push((index, rect, event_response));
+				continue;
+			}
+
+			// the resize handle is interacted with first, so that it wins over the body
+			let handle_height = (0.25 * rect.height()).min(6.0);
+			let handle_rect = Rect::from_min_max(pos2(rect.left(), rect.bottom() - handle_height), rect.max);
+			let handles = is_last.then(|| (handle_rect, self.grid.id.with(("resize", index)), true));
+			for (part_rect, part_id, resize) in handles.into_iter().chain([(rect, event_id, false)]) {
+				let part_response = ui.interact(part_rect, part_id, Sense::drag());
+				if part_response.hovered() && drag.is_none() {
+					let icon = if resize { CursorIcon::ResizeVertical } else { CursorIcon::Grab };
+					ui.ctx().set_cursor_icon(icon);
+				}
+				if part_response.drag_started() {
+					let event = &self.grid.events[index];
+					drag = Some(EventDrag { index, resize, start: event.start, end: event.end, delta: Vec2::ZERO });
+				}
+				if !resize {
+					self.event_responses.push((index, rect, part_response));
+				}
+			}
+		}
+
+		// The segments of a moved event get other ids in other columns, so the drag is followed
+		// with the pointer until it is released.
+		let (pointer_delta, pointer_down) = ui.input(|i| (i.pointer.delta(), i.pointer.primary_down()));
+		match &mut drag {
+			Some(active) if pointer_down && active.index < self.grid.events.len() => {
+				active.delta += pointer_delta;
+				let icon = if active.resize { CursorIcon::ResizeVertical } else { CursorIcon::Grabbing };
+				ui.ctx().set_cursor_icon(icon);
+				let (start, end) = self.dragged_times(active);
+				let event = &mut self.grid.events[active.index];
+				if (start, end) != (event.start, event.end) {
+					(event.start, event.end) = (start, end);
+					response.mark_changed();
+				}
+			}
+			_ => drag = None,
+		}
+		self.dragged_event = drag.map(|drag| drag.index);
+		ui.data_mut(|d| match drag {
+			Some(drag) => d.insert_temp(drag_id, drag),
+			None => d.remove::<EventDrag>(drag_id),
+		});
+		response
+	}
+
+	/// The new times of a dragged event, snapped to `snap_minutes`, and kept inside the shown
+	/// days and hours so that it can always be dragged back.
+	fn dragged_times(&self, drag: &EventDrag) -> (NaiveDateTime, NaiveDateTime) {
+		let minutes = Duration::minutes((drag.delta.y / self.hour_height * 60.0) as i64);
+		if drag.resize {
+			let min_end = drag.start + Duration::minutes(self.grid.snap_minutes.into());
+			let last_day = self.first_day + Duration::days((self.grid.days - 1).into());
+			let max_end = self.shown_hours(last_day).end.max(drag.end);
+			return (drag.start, self.snap(drag.end + minutes).clamp(min_end, max_end));
+		}
+		// keep the start inside the shown days
+		let start_day = (drag.start.date() - self.first_day).num_days();
+		let days = ((drag.delta.x / self.column_width()).round() as i64)
+			.clamp(-start_day, self.grid.days as i64 - 1 - start_day);
+		let shown = self.shown_hours(drag.start.date() + Duration::days(days));
+		let duration = drag.end - drag.start;
+		// an event longer than the shown hours starts with them
+		let start = self
+			.snap(drag.start + Duration::days(days) + minutes)
+			.min(shown.end - duration)
+			.max(shown.start);
+		(start, start + duration)
+	}
+
+	/// The start and end of the hours shown on `day`.
+	fn shown_hours(&self, day: NaiveDate) -> Range<NaiveDateTime> {
+		let midnight = day.and_time(NaiveTime::MIN);
+		midnight + Duration::hours(self.grid.hours.start.into())..midnight + Duration::hours(self.grid.hours.end.into())
+	}
+
+	/// Round to the nearest multiple of `snap_minutes` since midnight.
+	fn snap(&self, date: NaiveDateTime) -> NaiveDateTime {
+		let step = self.grid.snap_minutes as f32;
+		let minutes = date.time().num_seconds_from_midnight() as f32 / 60.0;
+		let snapped = ((minutes / step).round() * step) as i64;
+		date.date().and_time(NaiveTime::MIN) + Duration::minutes(snapped)
+	}
+
+	fn column_width(&self) -> f32 {
+		(self.rect.width() - self.gutter_width) / self.grid.days as f32
+	}
+
+	fn column_left(&self, day: u32) -> f32 {
+		self.rect.left() + self.gutter_width + day as f32 * self.column_width()
+	}
+
+	/// The y coordinate of a time on the given day, which may lie outside the grid.
+	fn time_y(&self, day: NaiveDate, date: NaiveDateTime) -> f32 {
+		let shown_start = day.and_time(NaiveTime::MIN) + Duration::hours(self.grid.hours.start.into());
+		let hours = (date - shown_start).num_seconds() as f32 / 3600.0;
+		self.rect.top() + self.header_height + hours * self.hour_height
+	}
+
+	/// Split the events into one segment per shown day, with overlapping events of a day side
+	/// by side.
+	fn event_segments(&self) -> Vec<EventSegment> {
+		let mut segments = Vec::new();
+		let grid_top = self.rect.top() + self.header_height;
+		for day in 0..self.grid.days {
+			let date = self.first_day + Duration::days(day.into());
+			let Range { start: shown_start, end: shown_end } = self.shown_hours(date);
+			let next_shown_start = shown_start + Duration::days(1);
+
+			let mut day_events: Vec<usize> = (0..self.grid.events.len())
+				.filter(|&index| {
+					let event = &self.grid.events[index];
+					event.start < shown_end && event.end > shown_start
+				})
+				.collect();
+			day_events.sort_by_key(|&index| self.grid.events[index].start);
+
+			// greedily put each event into the first lane, which is free at its start
+			let mut lane_ends: Vec<NaiveDateTime> = Vec::new();
+			let mut lanes = Vec::new();
+			for &index in &day_events {
+				let event = &self.grid.events[index];
+				let lane = match lane_ends.iter().position(|end| *end <= event.start) {
+					Some(lane) => lane,
+					None => {
+						lane_ends.push(event.end);
+						lane_ends.len() - 1
+					}
+				};
+				lane_ends[lane] = event.end;
+				lanes.push(lane);
+			}
+
+			let lane_width = self.column_width() / lane_ends.len().max(1) as f32;
+			for (&index, lane) in day_events.iter().zip(lanes) {
+				let event = &self.grid.events[index];
+				let left = self.column_left(day) + lane as f32 * lane_width;
+				let top = self.time_y(date, event.start.max(shown_start)).max(grid_top);
+				let bottom = self.time_y(date, event.end.min(shown_end)).min(self.rect.bottom());
+				let rect = Rect::from_min_max(pos2(left + 1.0, top), pos2(left + lane_width - 1.0, bottom));
+				let is_last = day + 1 == self.grid.days || event.end <= next_shown_start;
+				segments.push(EventSegment { index, day, rect, is_last });
+			}
+		}
+		segments
+	}
+}
+
+impl Placed for LaidOutTimeGrid<'_> {
+	fn placed_rect(&self) -> Rect {
+		self.rect
+	}
+
+	fn place(&mut self, rect: Rect) {
+		self.rect = rect;
+	}
+}
+
+impl Paint<LaidOutTimeGrid<'_>> for Ui {
+	fn paint(&mut self, logrid: &LaidOutTimeGrid<'_>, _response: &Response) {
+		let LaidOutTimeGrid { grid, first_day, rect, gutter_width, header_height, hour_height, .. } = logrid;
+		if !self.is_rect_visible(*rect) {
+			return;
+		}
+		let painter = self.painter();
+		let visuals = self.visuals();
+		let line_stroke = visuals.widgets.noninteractive.bg_stroke;
+		let small_font = TextStyle::Small.resolve(self.style());
+		let body_font = TextStyle::Body.resolve(self.style());
+		let grid_top = rect.top() + header_height;
+		let today = Local::now().date_naive();
+
+		for day in 0..grid.days {
+			let date = *first_day + Duration::days(day.into());
+			let left = logrid.column_left(day);
+			let column = Rect::from_min_max(pos2(left, grid_top), pos2(left + logrid.column_width(), rect.bottom()));
+			if date == today {
+				painter.rect_filled(column, 0.0, visuals.selection.bg_fill.linear_multiply(0.15));
+			}
+			painter.vline(left, rect.top()..=rect.bottom(), line_stroke);
+
+			let mut color = visuals.text_color();
+			if grid.highlight_weekend && (grid.weekend_func)(&date.and_time(NaiveTime::MIN)) {
+				color = grid.weekend_color;
+			} else if date == today {
+				color = visuals.strong_text_color();
+			}
+			let name = format!("{} {}", grid.locale.weekday_name(date.weekday()), date.day());
+			let center = pos2(column.center().x, rect.top() + 0.5 * header_height);
+			painter.text(center, Align2::CENTER_CENTER, name, body_font.clone(), color);
+		}
+
+		for (i, hour) in grid.hours.clone().enumerate() {
+			let y = grid_top + i as f32 * hour_height;
+			painter.hline(rect.left() + gutter_width..=rect.right(), y, line_stroke);
+			let label_pos = pos2(rect.left() + gutter_width - visuals.clip_rect_margin, y);
+			let label = format!("{hour:02}:00");
+			painter.text(label_pos, Align2::RIGHT_TOP, label, small_font.clone(), visuals.weak_text_color());
+		}
+
+		for (index, event_rect, event_response) in &logrid.event_responses {
+			let event = &grid.events[*index];
+			let interact_visuals = self.style().interact(event_response);
+			let stroke = if event_response.hovered() || logrid.dragged_event == Some(*index) {
+				interact_visuals.fg_stroke
+			} else {
+				Stroke::NONE
+			};
+			painter.rect(*event_rect, interact_visuals.rounding, event.color, stroke);
+
+			let text_color = if Rgba::from(event.color).intensity() > 0.5 { Color32::BLACK } else { Color32::WHITE };
+			let text = format!("{}\n{}–{}", event.title, event.start.format("%H:%M"), event.end.format("%H:%M"));
+			painter.with_clip_rect(event_rect.intersect(painter.clip_rect())).text(
+				event_rect.left_top() + Vec2::splat(2.0),
+				Align2::LEFT_TOP,
+				text,
+				small_font.clone(),
+				text_color,
+			);
+		}
+	}
+}
@@ -0,0 +1,675 @@
+//! A Gantt chart: rows of bars along a horizontal date axis, with dependency arrows between
+//! bars and a line marking the current time.
+//!
+//! The axis is zoomed with `Ctrl` and the scroll wheel or a pinch, from hours up to decades,
+//! and panned by scrolling sideways or dragging the background. The tick labels adapt to the
+//! zoom level. Bars are moved by dragging them and resized by dragging their ends.
+//!
+//! ```no_run
+//! use eframe::egui::Ui;
+//! use grus_gui_lib::timeline::{Dependency, Timeline, TimelineRow};
+//! use grus_gui_lib::{Create, Paint, WidgetPlacer};
+//!
+//! fn draw_plan(ui: &mut Ui, rows: &mut [TimelineRow], dependencies: &[Dependency]) {
+//!     let mut placer = WidgetPlacer::new(ui);
+//!     let mut timeline = placer.create(Timeline::new("project_plan", rows).dependencies(dependencies));
+//!     let response = timeline.interact(ui);
+//!     ui.paint(&timeline, &response);
+//! }
+//! ```
+
+use std::hash::Hash;
+
+use chrono::naive::NaiveDateTime;
+use chrono::{prelude::*, Duration, Months};
+use eframe::egui::{
+	pos2, style::TextStyle, vec2, Align2, Color32, CursorIcon, Id, Pos2, Rect, Response, Rgba, Sense, Shape,
+	Stroke, Ui, Vec2, WidgetText,
+};
+
+use crate::calendar::{days_since_week_start, Locale};
+use crate::{Create, Paint, Placed, WidgetPlacer};
+
+/// A bar spanning the time from `start` to `end`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimelineBar {
+	pub label: String,
+	pub start: NaiveDateTime,
+	pub end: NaiveDateTime,
+	pub color: Color32,
+	/// The bar can't be moved or resized.
+	pub locked: bool,
+}
+
+impl TimelineBar {
+	pub fn new(label: impl ToString, start: NaiveDateTime, end: NaiveDateTime) -> Self {
+		Self {
+			label: label.to_string(),
+			start,
+			end,
+			color: Color32::LIGHT_BLUE,
+			locked: false,
+		}
+	}
+
+	pub fn color(mut self, color: Color32) -> Self {
+		self.color = color;
+		self
+	}
+
+	pub fn locked(mut self, locked: bool) -> Self {
+		self.locked = locked;
+		self
+	}
+}
+
+/// A labeled row of bars.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TimelineRow {
+	pub label: String,
+	pub bars: Vec<TimelineBar>,
+}
+
+impl TimelineRow {
+	pub fn new(label: impl ToString) -> Self {
+		Self {
+			label: label.to_string(),
+			bars: Vec::new(),
+		}
+	}
+
+	pub fn bar(mut self, bar: TimelineBar) -> Self {
+		self.bars.push(bar);
+		self
+	}
+}
+
+/// Refers to the `bar`th bar of the `row`th row.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BarRef {
+	pub row: usize,
+	pub bar: usize,
+}
+
+/// An arrow from the end of the bar `from` to the start of the bar `to`, which depends on it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Dependency {
+	pub from: BarRef,
+	pub to: BarRef,
+}
+
+/// The visible part of the axis.
+#[derive(Clone, Copy, Debug)]
+struct TimelineView {
+	start: NaiveDateTime,
+	seconds_per_point: f64,
+}
+
+impl TimelineView {
+	/// From a few seconds per point, showing parts of an hour, to decades across a screen.
+	const MIN_SECONDS_PER_POINT: f64 = 10.0;
+	const MAX_SECONDS_PER_POINT: f64 = 2.0e6;
+}
+
+/// Default values of fields are:
+/// - shown range: all bars, or the current week without any
+/// - row_height: one and a half times the height of a button
+/// - show_today: `true`
+/// - locale: [`Locale::EN`]
+/// - first_weekday: taken from the locale, used for weekly ticks
+#[must_use = "You should lay out this widget with a `WidgetPlacer`"]
+pub struct Timeline<'a, 'b> {
+	id: Id,
+	rows: &'a mut [TimelineRow],
+	dependencies: &'b [Dependency],
+	initial_range: Option<(NaiveDateTime, NaiveDateTime)>,
+	row_height: Option<f32>,
+	show_today: bool,
+	locale: Locale,
+	// when set, overrides the locale's first day of the week.
+	first_weekday: Option<Weekday>,
+}
+
+impl<'a, 'b> Timeline<'a, 'b> {
+	/// Create new timeline with unique id and mutable reference to the rows of bars.
+	pub fn new<T: Hash>(id: T, rows: &'a mut [TimelineRow]) -> Self {
+		Self {
+			id: Id::new(id),
+			rows,
+			dependencies: &[],
+			initial_range: None,
+			row_height: None,
+			show_today: true,
+			locale: Locale::default(),
+			first_weekday: None,
+		}
+	}
+
+	/// Draw an arrow for each of these dependencies.
+	pub fn dependencies(mut self, dependencies: &'b [Dependency]) -> Self {
+		self.dependencies = dependencies;
+		self
+	}
+
+	/// Show the time from `start` to `end` until the user zooms or pans.
+	pub fn initial_range(mut self, start: NaiveDateTime, end: NaiveDateTime) -> Self {
+		self.initial_range = Some((start, end));
+		self
+	}
+
+	/// Set the height of a row.
+	pub fn row_height(mut self, height: f32) -> Self {
+		self.row_height = Some(height);
+		self
+	}
+
+	/// If flag is set to true then a vertical line marks the current time.
+	/// Default is true
+	pub fn show_today(mut self, flag: bool) -> Self {
+		self.show_today = flag;
+		self
+	}
+
+	/// Set the language of month names and the first day of the week.
+	pub fn locale(mut self, locale: Locale) -> Self {
+		self.locale = locale;
+		self
+	}
+
+	/// If flag is set to true then weekly ticks will be on sundays otherwise mondays.
+	/// Default is taken from the locale
+	pub fn sunday_first(self, flag: bool) -> Self {
+		self.first_weekday(if flag { Weekday::Sun } else { Weekday::Mon })
+	}
+
+	/// Set the first day of the week, overriding the one of the locale.
+	pub fn first_weekday(mut self, weekday: Weekday) -> Self {
+		self.first_weekday = Some(weekday);
+		self
+	}
+
+	/// The range to show initially, with a margin around the bars.
+	fn fit_range(&self) -> (NaiveDateTime, NaiveDateTime) {
+		if let Some(range) = self.initial_range {
+			return range;
+		}
+		let bars = self.rows.iter().flat_map(|row| &row.bars);
+		let start = bars.clone().map(|bar| bar.start).min();
+		let end = bars.map(|bar| bar.end).max();
+		match (start, end) {
+			(Some(start), Some(end)) if start < end => {
+				let margin = (end - start) / 20;
+				(start - margin, end + margin)
+			}
+			_ => {
+				let today = Local::now().date_naive().and_time(NaiveTime::MIN);
+				(today - Duration::days(3), today + Duration::days(4))
+			}
+		}
+	}
+}
+
+impl<'a, 'b> Create<Timeline<'a, 'b>> for WidgetPlacer<'_> {
+	type LaidOutWidget = LaidOutTimeline<'a, 'b>;
+	#[track_caller]
+	fn create(&mut self, timeline: Timeline<'a, 'b>) -> LaidOutTimeline<'a, 'b> {
+		let interact_height = self.style.spacing.interact_size.y;
+		let row_height = timeline.row_height.unwrap_or(1.5 * interact_height);
+		let spacing = self.style.spacing.item_spacing.x;
+
+		let mut width = self.placer.available_size().x;
+		if !width.is_finite() {
+			width = 800.0;
+		}
+		let label_width = timeline
+			.rows
+			.iter()
+			.map(|row| {
+				let text = WidgetText::from(row.label.as_str());
+				self.into_galley(text, Some(false), f32::INFINITY, TextStyle::Body).size().x
+			})
+			.fold(0.0, f32::max);
+		let label_width = (label_width + 2.0 * spacing).min(width / 3.0);
+		let height = interact_height + timeline.rows.len() as f32 * row_height;
+		let rect = self.allocate_space(Vec2::new(width, height));
+
+		let view_id = timeline.id.with("view");
+		let view = self.context.data(|d| d.get_temp::<TimelineView>(view_id)).unwrap_or_else(|| {
+			let (start, end) = timeline.fit_range();
+			let seconds = (end - start).num_seconds() as f64;
+			let seconds_per_point = (seconds / (width - label_width).max(1.0) as f64)
+				.clamp(TimelineView::MIN_SECONDS_PER_POINT, TimelineView::MAX_SECONDS_PER_POINT);
+			TimelineView { start, seconds_per_point }
+		});
+
+		LaidOutTimeline {
+			timeline,
+			rect,
+			label_width,
+			axis_height: interact_height,
+			row_height,
+			view,
+			bar_responses: Vec::new(),
+		}
+	}
+}
+
+/// The part of a bar being dragged.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BarPart {
+	Start,
+	End,
+	Body,
+}
+
+/// A bar being dragged, with its times when the drag started.
+#[derive(Clone, Copy, Debug)]
+struct BarDrag {
+	bar: BarRef,
+	part: BarPart,
+	start: NaiveDateTime,
+	end: NaiveDateTime,
+	delta: f32,
+}
+
+pub struct LaidOutTimeline<'a, 'b> {
+	timeline: Timeline<'a, 'b>,
+	rect: Rect,
+	label_width: f32,
+	axis_height: f32,
+	row_height: f32,
+	view: TimelineView,
+	// set by `interact`, how the bars were interacted with.
+	bar_responses: Vec<(BarRef, Response)>,
+}
+
+impl LaidOutTimeline<'_, '_> {
+	pub fn reposition(&mut self, y: f32) {
+		self.rect = self.rect.translate(Vec2::new(0., y - self.rect.top()));
+	}
+
+	/// Interact with the bars and the axis, moving and resizing the dragged bar and zooming or
+	/// panning the axis. The response is marked as changed while a bar changes.
+	pub fn interact(&mut self, ui: &mut Ui) -> Response {
+		let drag_id = self.timeline.id.with("drag");
+		let mut drag = ui.data(|d| d.get_temp::<BarDrag>(drag_id));
+		let mut changed = false;
+
+		self.bar_responses.clear();
+		for bar_ref in self.bar_refs() {
+			let rect = self.bar_rect(bar_ref).intersect(self.data_rect());
+			let bar_id = self.timeline.id.with(("bar", bar_ref));
+			if !rect.is_positive() {
+				continue;
+			}
+			if self.bar(bar_ref).locked {
+				self.bar_responses.push((bar_ref, ui.interact(rect, bar_id, Sense::hover())));
+				continue;
+			}
+
+			// the handles are interacted with first, so that they win over the body
+			let handle_width = (0.25 * rect.width()).min(6.0);
+			let parts = [
+				(Rect::from_min_max(rect.min, pos2(rect.left() + handle_width, rect.bottom())), BarPart::Start),
+				(Rect::from_min_max(pos2(rect.right() - handle_width, rect.top()), rect.max), BarPart::End),
+				(rect, BarPart::Body),
+			];
+			for (part_rect, part) in parts {
+				let part_response = ui.interact(part_rect, bar_id.with(part as u8), Sense::drag());
+				if part_response.hovered() || part_response.dragged() {
+					let icon = match (part, part_response.dragged()) {
+						(BarPart::Body, false) => CursorIcon::Grab,
+						(BarPart::Body, true) => CursorIcon::Grabbing,
+						_ => CursorIcon::ResizeHorizontal,
+					};
+					ui.ctx().set_cursor_icon(icon);
+				}
+				if part_response.drag_started() {
+					let bar = self.bar(bar_ref);
+					drag = Some(BarDrag { bar: bar_ref, part, start: bar.start, end: bar.end, delta: 0.0 });
+				}
+				match &mut drag {
+					Some(active) if active.bar == bar_ref && active.part == part => {
+						if part_response.dragged() {
+							active.delta += part_response.drag_delta().x;
+							let (start, end) = self.dragged_times(active);
+							let bar = &mut self.timeline.rows[bar_ref.row].bars[bar_ref.bar];
+							if (start, end) != (bar.start, bar.end) {
+								(bar.start, bar.end) = (start, end);
+								changed = true;
+							}
+						}
+						if part_response.drag_released() {
+							drag = None;
+						}
+					}
+					_ => {}
+				}
+				if part == BarPart::Body {
+					self.bar_responses.push((bar_ref, part_response));
+				}
+			}
+		}
+		ui.data_mut(|d| match drag {
+			Some(drag) => d.insert_temp(drag_id, drag),
+			None => d.remove::<BarDrag>(drag_id),
+		});
+
+		// the background is interacted with last, so that dragging it doesn't steal bar drags
+		let mut response = ui.interact(self.rect, self.timeline.id.with("background"), Sense::drag());
+		self.pan_and_zoom(ui, &response);
+		if changed {
+			response.mark_changed();
+		}
+		response
+	}
+
+	fn pan_and_zoom(&mut self, ui: &Ui, response: &Response) {
+		let mut pan = -response.drag_delta().x;
+		if response.hovered() {
+			let (zoom, scroll) = ui.input(|i| (i.zoom_delta(), i.scroll_delta.x));
+			pan -= scroll;
+			if zoom != 1.0 {
+				// keep the time under the pointer in place
+				let anchor_x = response.hover_pos().map_or(self.data_rect().center().x, |pos| pos.x);
+				let anchor = self.x_to_time(anchor_x);
+				self.view.seconds_per_point = (self.view.seconds_per_point / zoom as f64)
+					.clamp(TimelineView::MIN_SECONDS_PER_POINT, TimelineView::MAX_SECONDS_PER_POINT);
+				let offset = seconds((anchor_x - self.data_rect().left()) as f64 * self.view.seconds_per_point);
+				self.view.start = anchor.checked_sub_signed(offset).unwrap_or(self.view.start);
+			}
+		}
+		if pan != 0.0 {
+			let offset = seconds(pan as f64 * self.view.seconds_per_point);
+			self.view.start = self.view.start.checked_add_signed(offset).unwrap_or(self.view.start);
+		}
+		ui.data_mut(|d| d.insert_temp(self.timeline.id.with("view"), self.view));
+	}
+
+	/// The new times of a dragged bar, snapped to the current tick unit.
+	fn dragged_times(&self, drag: &BarDrag) -> (NaiveDateTime, NaiveDateTime) {
+		let offset = seconds(drag.delta as f64 * self.view.seconds_per_point);
+		let snap = |date: NaiveDateTime| self.tick_unit().snap(date.checked_add_signed(offset).unwrap_or(date));
+		let min_length = self.tick_unit().snap_step();
+		match drag.part {
+			BarPart::Start => (snap(drag.start).min(drag.end - min_length), drag.end),
+			BarPart::End => (drag.start, snap(drag.end).max(drag.start + min_length)),
+			BarPart::Body => {
+				let start = snap(drag.start);
+				(start, start + (drag.end - drag.start))
+			}
+		}
+	}
+
+	fn bar(&self, bar_ref: BarRef) -> &TimelineBar {
+		&self.timeline.rows[bar_ref.row].bars[bar_ref.bar]
+	}
+
+	fn bar_refs(&self) -> Vec<BarRef> {
+		let rows = self.timeline.rows.iter().enumerate();
+		rows.flat_map(|(row, bars)| (0..bars.bars.len()).map(move |bar| BarRef { row, bar })).collect()
+	}
+
+	/// The area right of the row labels and below the axis.
+	fn data_rect(&self) -> Rect {
+		Rect::from_min_max(
+			pos2(self.rect.left() + self.label_width, self.rect.top() + self.axis_height),
+			self.rect.max,
+		)
+	}
+
+	fn bar_rect(&self, bar_ref: BarRef) -> Rect {
+		let bar = self.bar(bar_ref);
+		let top = self.data_rect().top() + bar_ref.row as f32 * self.row_height;
+		let padding = 0.15 * self.row_height;
+		let left = self.time_to_x(bar.start);
+		let right = self.time_to_x(bar.end).max(left + 2.0);
+		Rect::from_min_max(pos2(left, top + padding), pos2(right, top + self.row_height - padding))
+	}
+
+	fn time_to_x(&self, date: NaiveDateTime) -> f32 {
+		let seconds = (date - self.view.start).num_milliseconds() as f64 / 1000.0;
+		self.data_rect().left() + (seconds / self.view.seconds_per_point) as f32
+	}
+
+	fn x_to_time(&self, x: f32) -> NaiveDateTime {
+		let offset = seconds((x - self.data_rect().left()) as f64 * self.view.seconds_per_point);
+		self.view.start.checked_add_signed(offset).unwrap_or(self.view.start)
+	}
+
+	/// The finest tick unit, whose labels don't overlap.
+	fn tick_unit(&self) -> TickUnit {
+		let min_spacing = 80.0;
+		TickUnit::ALL
+			.into_iter()
+			.find(|unit| unit.approx_seconds() / self.view.seconds_per_point >= min_spacing)
+			.unwrap_or(TickUnit::Years(100))
+	}
+
+	fn week_start(&self) -> Weekday {
+		self.timeline.first_weekday.unwrap_or(self.timeline.locale.first_weekday)
+	}
+}
+
+impl Placed for LaidOutTimeline<'_, '_> {
+	fn placed_rect(&self) -> Rect {
+		self.rect
+	}
+
+	fn place(&mut self, rect: Rect) {
+		self.rect = rect;
+	}
+}
+
+impl Paint<LaidOutTimeline<'_, '_>> for Ui {
+	fn paint(&mut self, lotimeline: &LaidOutTimeline<'_, '_>, _response: &Response) {
+		let LaidOutTimeline { timeline, rect, axis_height, row_height, .. } = lotimeline;
+		if !self.is_rect_visible(*rect) {
+			return;
+		}
+		let visuals = self.visuals();
+		let line_stroke = visuals.widgets.noninteractive.bg_stroke;
+		let small_font = TextStyle::Small.resolve(self.style());
+		let body_font = TextStyle::Body.resolve(self.style());
+		let data_rect = lotimeline.data_rect();
+		let painter = self.painter();
+		let data_painter = painter.with_clip_rect(data_rect.intersect(painter.clip_rect()));
+		let axis_rect = Rect::from_min_max(pos2(data_rect.left(), rect.top()), pos2(rect.right(), data_rect.top()));
+		let axis_painter = painter.with_clip_rect(axis_rect.intersect(painter.clip_rect()));
+
+		// rows
+		for (i, row) in timeline.rows.iter().enumerate() {
+			let top = data_rect.top() + i as f32 * row_height;
+			if i % 2 == 1 {
+				let stripe = Rect::from_min_max(pos2(rect.left(), top), pos2(rect.right(), top + row_height));
+				painter.rect_filled(stripe, 0.0, visuals.faint_bg_color);
+			}
+			let label_pos = pos2(rect.left() + self.spacing().item_spacing.x, top + 0.5 * row_height);
+			let label_rect = Rect::from_min_max(rect.min, pos2(data_rect.left(), rect.bottom()));
+			let label_painter = painter.with_clip_rect(label_rect.intersect(painter.clip_rect()));
+			label_painter.text(label_pos, Align2::LEFT_CENTER, &row.label, body_font.clone(), visuals.text_color());
+		}
+		painter.vline(data_rect.left(), rect.top()..=rect.bottom(), line_stroke);
+		painter.hline(rect.left()..=rect.right(), data_rect.top(), line_stroke);
+
+		// ticks
+		let unit = lotimeline.tick_unit();
+		let week_start = lotimeline.week_start();
+		let mut tick = Some(unit.floor(lotimeline.view.start, week_start));
+		while let Some(date) = tick {
+			let x = lotimeline.time_to_x(date);
+			if x > data_rect.right() {
+				break;
+			}
+			data_painter.vline(x, data_rect.top()..=data_rect.bottom(), Stroke::new(1.0, visuals.faint_bg_color));
+			axis_painter.vline(x, rect.top()..=data_rect.top(), line_stroke);
+			let label = unit.label(date, &timeline.locale);
+			let label_pos = pos2(x + 3.0, rect.top() + 0.5 * axis_height);
+			axis_painter.text(label_pos, Align2::LEFT_CENTER, label, small_font.clone(), visuals.text_color());
+			tick = unit.next(date);
+		}
+
+		// bars
+		for (bar_ref, bar_response) in &lotimeline.bar_responses {
+			let bar = lotimeline.bar(*bar_ref);
+			let bar_rect = lotimeline.bar_rect(*bar_ref);
+			let interact_visuals = self.style().interact(bar_response);
+			let stroke = if bar_response.hovered() || bar_response.dragged() {
+				interact_visuals.fg_stroke
+			} else {
+				Stroke::NONE
+			};
+			data_painter.rect(bar_rect, interact_visuals.rounding, bar.color, stroke);
+
+			let text_color = if Rgba::from(bar.color).intensity() > 0.5 { Color32::BLACK } else { Color32::WHITE };
+			let text_painter = data_painter.with_clip_rect(bar_rect.intersect(data_painter.clip_rect()));
+			let text_pos = pos2(bar_rect.left().max(data_rect.left()) + 3.0, bar_rect.center().y);
+			text_painter.text(text_pos, Align2::LEFT_CENTER, &bar.label, small_font.clone(), text_color);
+		}
+
+		// dependencies
+		let arrow_stroke = Stroke::new(1.0, visuals.text_color());
+		for dependency in timeline.dependencies {
+			let bar_exists = |bar: BarRef| timeline.rows.get(bar.row).is_some_and(|row| bar.bar < row.bars.len());
+			if !bar_exists(dependency.from) || !bar_exists(dependency.to) {
+				continue;
+			}
+			let from = lotimeline.bar_rect(dependency.from).right_center();
+			let to = lotimeline.bar_rect(dependency.to).left_center();
+			for segment in arrow_path(from, to).windows(2) {
+				data_painter.line_segment([segment[0], segment[1]], arrow_stroke);
+			}
+			let head = vec![to, to - vec2(6.0, 3.0), to - vec2(6.0, -3.0)];
+			data_painter.add(Shape::convex_polygon(head, arrow_stroke.color, Stroke::NONE));
+		}
+
+		// today
+		if timeline.show_today {
+			let x = lotimeline.time_to_x(Local::now().naive_local());
+			data_painter.vline(x, data_rect.top()..=data_rect.bottom(), Stroke::new(2.0, visuals.error_fg_color));
+		}
+	}
+}
+
+/// The corners of an arrow going right from `from` and entering `to` from the left.
+fn arrow_path(from: Pos2, to: Pos2) -> Vec<Pos2> {
+	let gap = 8.0;
+	if to.x - from.x >= 2.0 * gap {
+		let middle_x = 0.5 * (from.x + to.x);
+		vec![from, pos2(middle_x, from.y), pos2(middle_x, to.y), to]
+	} else {
+		// go around the bars, between their rows
+		let middle_y = 0.5 * (from.y + to.y);
+		vec![
+			from,
+			pos2(from.x + gap, from.y),
+			pos2(from.x + gap, middle_y),
+			pos2(to.x - gap, middle_y),
+			pos2(to.x - gap, to.y),
+			to,
+		]
+	}
+}
+
+/// A duration of fractional seconds, saturating far beyond any date chrono can represent.
+fn seconds(seconds: f64) -> Duration {
+	Duration::milliseconds((seconds * 1000.0).clamp(-1e15, 1e15) as i64)
+}
+
+/// The distance between ticks on the axis.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TickUnit {
+	Hours(u32),
+	Days(u32),
+	Months(u32),
+	Years(i32),
+}
+
+impl TickUnit {
+	const ALL: [TickUnit; 13] = [
+		TickUnit::Hours(1),
+		TickUnit::Hours(3),
+		TickUnit::Hours(6),
+		TickUnit::Hours(12),
+		TickUnit::Days(1),
+		TickUnit::Days(7),
+		TickUnit::Months(1),
+		TickUnit::Months(3),
+		TickUnit::Years(1),
+		TickUnit::Years(5),
+		TickUnit::Years(10),
+		TickUnit::Years(50),
+		TickUnit::Years(100),
+	];
+
+	fn approx_seconds(self) -> f64 {
+		const DAY: f64 = 86400.0;
+		match self {
+			TickUnit::Hours(hours) => hours as f64 * 3600.0,
+			TickUnit::Days(days) => days as f64 * DAY,
+			TickUnit::Months(months) => months as f64 * 30.44 * DAY,
+			TickUnit::Years(years) => years as f64 * 365.25 * DAY,
+		}
+	}
+
+	/// The last tick at or before `date`.
+	fn floor(self, date: NaiveDateTime, week_start: Weekday) -> NaiveDateTime {
+		let day = date.date();
+		let floored = match self {
+			TickUnit::Hours(hours) => day.and_hms_opt(date.hour() / hours * hours, 0, 0),
+			TickUnit::Days(7) => {
+				let days = days_since_week_start(day.weekday(), week_start);
+				(day - Duration::days(days.into())).and_hms_opt(0, 0, 0)
+			}
+			TickUnit::Days(_) => day.and_hms_opt(0, 0, 0),
+			TickUnit::Months(months) => {
+				let month = (day.month() - 1) / months * months + 1;
+				NaiveDate::from_ymd_opt(day.year(), month, 1).and_then(|first| first.and_hms_opt(0, 0, 0))
+			}
+			TickUnit::Years(years) => {
+				let year = day.year().div_euclid(years) * years;
+				NaiveDate::from_ymd_opt(year, 1, 1).and_then(|first| first.and_hms_opt(0, 0, 0))
+			}
+		};
+		floored.unwrap_or(date)
+	}
+
+	/// The tick after `date`, which is a tick itself.
+	fn next(self, date: NaiveDateTime) -> Option<NaiveDateTime> {
+		match self {
+			TickUnit::Hours(hours) => date.checked_add_signed(Duration::hours(hours.into())),
+			TickUnit::Days(days) => date.checked_add_signed(Duration::days(days.into())),
+			TickUnit::Months(months) => date.checked_add_months(Months::new(months)),
+			TickUnit::Years(years) => date.checked_add_months(Months::new(years as u32 * 12)),
+		}
+	}
+
+	fn label(self, date: NaiveDateTime, locale: &Locale) -> String {
+		let day_label = || format!("{} {}", date.day(), locale.short_month_name(date.month()));
+		match self {
+			TickUnit::Hours(_) if date.hour() == 0 => day_label(),
+			TickUnit::Hours(_) => date.format("%H:%M").to_string(),
+			TickUnit::Days(_) => day_label(),
+			TickUnit::Months(_) if date.month() == 1 => format!("{} {}", locale.short_month_name(1), date.year()),
+			TickUnit::Months(_) => locale.short_month_name(date.month()).to_string(),
+			TickUnit::Years(_) => date.year().to_string(),
+		}
+	}
+
+	/// Dragged bars snap to multiples of this.
+	fn snap_step(self) -> Duration {
+		match self {
+			TickUnit::Hours(1) => Duration::minutes(15),
+			TickUnit::Hours(_) => Duration::hours(1),
+			_ => Duration::days(1),
+		}
+	}
+
+	fn snap(self, date: NaiveDateTime) -> NaiveDateTime {
+		let step = self.snap_step().num_seconds();
+		let midnight = date.date().and_time(NaiveTime::MIN);
+		let since_midnight = (date - midnight).num_seconds();
+		let snapped = (since_midnight + step / 2) / step * step;
+		midnight + Duration::seconds(snapped)
+	}
+}